@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE loan_details DROP CONSTRAINT IF EXISTS unique_block_loan_detail;
+ALTER TABLE loan_actions DROP CONSTRAINT IF EXISTS unique_block_loan_action;
+ALTER TABLE loan_details DROP COLUMN block_hash;
+ALTER TABLE loan_actions DROP COLUMN block_hash;
+
+ALTER TABLE events DROP CONSTRAINT IF EXISTS unique_block_tx_event;
+ALTER TABLE events DROP COLUMN event_order;
+ALTER TABLE events DROP COLUMN main_chain;
+ALTER TABLE events DROP COLUMN block_hash;
+ALTER TABLE events ADD CONSTRAINT unique_tx_event UNIQUE (tx_id, event_index);
@@ -0,0 +1,27 @@
+-- Rows indexed before this migration don't record their block, and loan rows don't even record
+-- their transaction, so they can't be rolled back on reorg. Drop them and reset the cursors of
+-- their processors so they are re-indexed with their block.
+TRUNCATE events, loan_actions, loan_details RESTART IDENTITY;
+DELETE FROM processor_status WHERE processor IN ('event_processor', 'lending_contract_processor');
+
+-- Record the block each derived row comes from, so rows from blocks that left the main chain
+-- can be found again
+ALTER TABLE events ADD COLUMN block_hash TEXT NOT NULL;
+ALTER TABLE events ADD COLUMN main_chain BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- Events are told apart by their position in the events of their block, several contracts
+-- of a transaction can emit events with the same index
+ALTER TABLE events ADD COLUMN event_order INTEGER NOT NULL;
+
+-- The same transaction can be included in a block of each fork
+ALTER TABLE events DROP CONSTRAINT IF EXISTS unique_tx_event;
+ALTER TABLE events DROP CONSTRAINT IF EXISTS events_tx_id_contract_address_event_index_key;
+ALTER TABLE events ADD CONSTRAINT unique_block_tx_event UNIQUE (block_hash, tx_id, event_order);
+
+ALTER TABLE loan_actions ADD COLUMN block_hash TEXT NOT NULL;
+ALTER TABLE loan_details ADD COLUMN block_hash TEXT NOT NULL;
+
+ALTER TABLE loan_actions
+  ADD CONSTRAINT unique_block_loan_action UNIQUE (block_hash, loan_subcontract_id, action_type);
+ALTER TABLE loan_details
+  ADD CONSTRAINT unique_block_loan_detail UNIQUE (block_hash, loan_subcontract_id);
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+CREATE TABLE processor_chain_status (
+    processor VARCHAR(50) NOT NULL,
+    chain_from BIGINT NOT NULL,
+    chain_to BIGINT NOT NULL,
+    block_hash TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    PRIMARY KEY (processor, chain_from, chain_to)
+);
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+CREATE INDEX idx_blocks_chain_height ON blocks(chain_from, chain_to, height);
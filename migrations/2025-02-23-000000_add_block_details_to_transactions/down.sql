@@ -0,0 +1,15 @@
+DROP INDEX IF EXISTS idx_transactions_block_timestamp;
+DROP INDEX IF EXISTS idx_transactions_block_hash;
+
+ALTER TABLE transactions DROP COLUMN gas_price;
+ALTER TABLE transactions DROP COLUMN gas_amount;
+ALTER TABLE transactions DROP COLUMN block_timestamp;
+
+-- Keep a single row per transaction, the one of the main chain block if any
+DELETE FROM transactions t USING transactions o
+WHERE t.tx_hash = o.tx_hash
+  AND t.block_hash <> o.block_hash
+  AND (o.main_chain, o.block_hash) > (t.main_chain, t.block_hash);
+
+ALTER TABLE transactions DROP CONSTRAINT transactions_pkey;
+ALTER TABLE transactions ADD PRIMARY KEY (tx_hash);
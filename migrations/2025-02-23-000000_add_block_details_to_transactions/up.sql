@@ -0,0 +1,10 @@
+-- A transaction may be included in several blocks of competing forks, keep one row per block
+ALTER TABLE transactions DROP CONSTRAINT transactions_pkey;
+ALTER TABLE transactions ADD PRIMARY KEY (tx_hash, block_hash);
+
+ALTER TABLE transactions ADD COLUMN block_timestamp TIMESTAMP NOT NULL;
+ALTER TABLE transactions ADD COLUMN gas_amount INTEGER NOT NULL;
+ALTER TABLE transactions ADD COLUMN gas_price NUMERIC NOT NULL;
+
+CREATE INDEX idx_transactions_block_hash ON transactions (block_hash);
+CREATE INDEX idx_transactions_block_timestamp ON transactions (block_timestamp);
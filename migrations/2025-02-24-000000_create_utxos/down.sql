@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS address_balances;
+DROP TABLE IF EXISTS utxos;
@@ -0,0 +1,27 @@
+-- Outputs of main chain transactions, spent or not
+CREATE TABLE utxos (
+    key TEXT PRIMARY KEY,
+    tx_id TEXT NOT NULL,
+    output_index INTEGER NOT NULL,
+    block_hash TEXT NOT NULL,
+    block_timestamp TIMESTAMP NOT NULL,
+    address TEXT NOT NULL,
+    atto_alph_amount NUMERIC NOT NULL,
+    tokens JSONB NOT NULL, -- Array of Token
+    lock_time BIGINT,
+    is_contract BOOLEAN NOT NULL,
+    spent_tx_id TEXT,
+    spent_block_hash TEXT
+);
+
+CREATE INDEX idx_utxos_unspent_address ON utxos (address) WHERE spent_tx_id IS NULL;
+CREATE INDEX idx_utxos_block_hash ON utxos (block_hash);
+CREATE INDEX idx_utxos_spent_block_hash ON utxos (spent_block_hash);
+
+-- Sum of the unspent outputs of each address, per token, ALPH having the all-zero token id
+CREATE TABLE address_balances (
+    address TEXT NOT NULL,
+    token_id TEXT NOT NULL,
+    amount NUMERIC NOT NULL,
+    PRIMARY KEY (address, token_id)
+);
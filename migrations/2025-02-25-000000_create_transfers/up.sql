@@ -0,0 +1,28 @@
+-- Outputs seen by the token transfer processor, to resolve the owner and amounts of the inputs
+-- spending them
+CREATE TABLE transfer_outputs (
+    key TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    atto_alph_amount NUMERIC NOT NULL,
+    tokens JSONB NOT NULL, -- Array of Token
+    block_hash TEXT NOT NULL
+);
+
+CREATE INDEX idx_transfer_outputs_block_hash ON transfer_outputs (block_hash);
+
+-- Net amount of ALPH (all-zero token id) or of a token received by an address in a transaction
+CREATE TABLE transfers (
+    tx_id TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    from_addresses TEXT[] NOT NULL,
+    to_address TEXT NOT NULL,
+    token_id TEXT NOT NULL,
+    amount NUMERIC NOT NULL,
+    PRIMARY KEY (tx_id, block_hash, to_address, token_id)
+);
+
+CREATE INDEX idx_transfers_to_address ON transfers (to_address, timestamp);
+CREATE INDEX idx_transfers_from_addresses ON transfers USING GIN (from_addresses);
+CREATE INDEX idx_transfers_token_id ON transfers (token_id, timestamp);
+CREATE INDEX idx_transfers_block_hash ON transfers (block_hash);
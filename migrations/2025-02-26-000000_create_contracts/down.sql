@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS contract_states;
+DROP TABLE IF EXISTS contracts;
@@ -0,0 +1,38 @@
+-- Contracts created in main chain transactions. A subcontract may be created again at the same
+-- address once destroyed, hence one row per creation.
+CREATE TABLE contracts (
+    id SERIAL PRIMARY KEY,
+    address TEXT NOT NULL,
+    contract_id TEXT NOT NULL,
+    parent_address TEXT,
+    creator_address TEXT,
+    code_hash TEXT,
+    std_interface_id TEXT,
+    creation_block_hash TEXT NOT NULL,
+    creation_tx_id TEXT NOT NULL,
+    creation_timestamp TIMESTAMP NOT NULL,
+    destruction_block_hash TEXT,
+    destruction_tx_id TEXT,
+    destruction_timestamp TIMESTAMP,
+    CONSTRAINT unique_contract_creation UNIQUE (address, creation_block_hash)
+);
+
+CREATE INDEX idx_contracts_address ON contracts (address);
+CREATE INDEX idx_contracts_parent_address ON contracts (parent_address);
+CREATE INDEX idx_contracts_creation_block_hash ON contracts (creation_block_hash);
+CREATE INDEX idx_contracts_destruction_block_hash ON contracts (destruction_block_hash);
+
+-- Contract states read from the node after the transactions touching them
+CREATE TABLE contract_states (
+    address TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    tx_id TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    code_hash TEXT NOT NULL,
+    imm_fields JSONB NOT NULL,
+    mut_fields JSONB NOT NULL,
+    asset JSONB NOT NULL,
+    PRIMARY KEY (address, block_hash, tx_id)
+);
+
+CREATE INDEX idx_contract_states_block_hash ON contract_states (block_hash);
@@ -0,0 +1,21 @@
+-- Tokens seen in transaction outputs. The metadata is read through the fungible token standard
+-- methods, and left empty for tokens not implementing it. `is_fungible` is NULL while the node
+-- rejects the calls, the metadata is then read again at the next sighting of the token.
+CREATE TABLE tokens (
+    token_id TEXT PRIMARY KEY,
+    contract_address TEXT,
+    name TEXT,
+    symbol TEXT,
+    decimals INTEGER,
+    total_supply NUMERIC,
+    is_fungible BOOLEAN,
+    first_seen_block_hash TEXT,
+    first_seen_timestamp TIMESTAMP
+);
+
+CREATE INDEX idx_tokens_symbol ON tokens (symbol);
+CREATE INDEX idx_tokens_first_seen_block_hash ON tokens (first_seen_block_hash);
+
+-- ALPH is not issued by a contract, it is listed under the all-zero token id
+INSERT INTO tokens (token_id, name, symbol, decimals, is_fungible)
+VALUES ('0000000000000000000000000000000000000000000000000000000000000000', 'Alephium', 'ALPH', 18, TRUE);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS nft_owners;
+DROP TABLE IF EXISTS nft_movements;
+DROP TABLE IF EXISTS nfts;
+DROP TABLE IF EXISTS nft_collections;
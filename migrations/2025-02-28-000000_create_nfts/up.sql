@@ -0,0 +1,51 @@
+-- NFT collections, identified by the standard interface id of their contract
+CREATE TABLE nft_collections (
+    address TEXT PRIMARY KEY,
+    contract_id TEXT NOT NULL,
+    std_interface_id TEXT NOT NULL,
+    collection_uri TEXT,
+    total_supply NUMERIC,
+    creation_block_hash TEXT NOT NULL,
+    creation_tx_id TEXT NOT NULL,
+    creation_timestamp TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_nft_collections_creation_block_hash ON nft_collections (creation_block_hash);
+
+-- NFTs minted as subcontracts of a collection, the token id being the NFT contract id
+CREATE TABLE nfts (
+    token_id TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    collection_address TEXT,
+    nft_index NUMERIC,
+    token_uri TEXT,
+    minted_block_hash TEXT NOT NULL,
+    minted_tx_id TEXT NOT NULL,
+    minted_timestamp TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_nfts_collection_address ON nfts (collection_address);
+CREATE INDEX idx_nfts_minted_block_hash ON nfts (minted_block_hash);
+
+-- Every output an NFT was sent to, current owners being derived from the latest one
+CREATE TABLE nft_movements (
+    token_id TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    tx_id TEXT NOT NULL,
+    tx_index INTEGER NOT NULL,
+    to_address TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (token_id, block_hash, tx_id)
+);
+
+CREATE INDEX idx_nft_movements_block_hash ON nft_movements (block_hash);
+
+CREATE TABLE nft_owners (
+    token_id TEXT PRIMARY KEY,
+    owner_address TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    tx_id TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_nft_owners_owner_address ON nft_owners (owner_address);
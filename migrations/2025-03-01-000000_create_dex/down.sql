@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS dex_reserves;
+DROP TABLE IF EXISTS dex_liquidity_actions;
+DROP TABLE IF EXISTS dex_swaps;
+DROP TABLE IF EXISTS dex_pairs;
@@ -0,0 +1,64 @@
+-- Pairs created by a DEX factory
+CREATE TABLE dex_pairs (
+    address TEXT PRIMARY KEY,
+    factory_address TEXT NOT NULL,
+    token0_id TEXT NOT NULL,
+    token1_id TEXT NOT NULL,
+    block_hash TEXT NOT NULL,
+    tx_id TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL
+);
+
+CREATE INDEX idx_dex_pairs_factory_address ON dex_pairs (factory_address);
+CREATE INDEX idx_dex_pairs_block_hash ON dex_pairs (block_hash);
+
+-- Swaps, events being identified by their block and position among the block events
+CREATE TABLE dex_swaps (
+    block_hash TEXT NOT NULL,
+    event_order INTEGER NOT NULL,
+    pair_address TEXT NOT NULL,
+    tx_id TEXT NOT NULL,
+    trader TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    amount0_in NUMERIC NOT NULL,
+    amount1_in NUMERIC NOT NULL,
+    amount0_out NUMERIC NOT NULL,
+    amount1_out NUMERIC NOT NULL,
+    price NUMERIC,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (block_hash, event_order)
+);
+
+CREATE INDEX idx_dex_swaps_pair_address_timestamp ON dex_swaps (pair_address, timestamp);
+CREATE INDEX idx_dex_swaps_trader ON dex_swaps (trader);
+
+-- Liquidity added (mint) or removed (burn)
+CREATE TABLE dex_liquidity_actions (
+    block_hash TEXT NOT NULL,
+    event_order INTEGER NOT NULL,
+    pair_address TEXT NOT NULL,
+    tx_id TEXT NOT NULL,
+    action_type SMALLINT NOT NULL,
+    sender TEXT NOT NULL,
+    amount0 NUMERIC NOT NULL,
+    amount1 NUMERIC NOT NULL,
+    liquidity NUMERIC NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (block_hash, event_order)
+);
+
+CREATE INDEX idx_dex_liquidity_actions_pair_address ON dex_liquidity_actions (pair_address);
+
+-- Reserves of the pairs after each sync
+CREATE TABLE dex_reserves (
+    block_hash TEXT NOT NULL,
+    event_order INTEGER NOT NULL,
+    pair_address TEXT NOT NULL,
+    tx_id TEXT NOT NULL,
+    reserve0 NUMERIC NOT NULL,
+    reserve1 NUMERIC NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (block_hash, event_order)
+);
+
+CREATE INDEX idx_dex_reserves_pair_address_timestamp ON dex_reserves (pair_address, timestamp);
@@ -0,0 +1,476 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ContractEventByBlockHash, EventField, EventFieldType};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AbiError {
+    /// The artifact could not be read.
+    #[error("Could not read artifact: {0}")]
+    Io(#[from] std::io::Error),
+    /// The artifact is not valid JSON or doesn't have the expected shape.
+    #[error("Invalid artifact: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A field type of the artifact is not a Ralph type.
+    #[error("Unsupported type: {0}")]
+    UnsupportedType(String),
+    /// An event signature has a different number of field names and types.
+    #[error("Event {event} has {names} field names but {types} field types")]
+    FieldCountMismatch { event: String, names: usize, types: usize },
+    /// The contract doesn't define an event with the index of the event to decode.
+    #[error("Unknown event index {0}")]
+    UnknownEvent(i32),
+    /// The event doesn't have as many fields as its signature.
+    #[error("Event {event} expects {expected} fields, found {found}")]
+    FieldCount { event: String, expected: usize, found: usize },
+    /// A field of the event doesn't have the type given by the signature.
+    #[error("Field {field} of event {event} expects {expected:?}, found {found:?}")]
+    FieldType { event: String, field: String, expected: EventFieldType, found: EventFieldType },
+    /// A field value couldn't be parsed as its type.
+    #[error("Invalid value {value:?} for field {field} of event {event}")]
+    InvalidValue { event: String, field: String, value: String },
+}
+
+pub type Result<T> = std::result::Result<T, AbiError>;
+
+/// A Ralph type, as written in artifacts: `U256`, `Address`, `[U256;2]`, ...
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiType {
+    Bool,
+    I256,
+    U256,
+    ByteVec,
+    Address,
+    Array(Box<AbiType>, usize),
+}
+
+impl FromStr for AbiType {
+    type Err = AbiError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let unsupported = || AbiError::UnsupportedType(s.to_string());
+        match s.trim() {
+            "Bool" => Ok(AbiType::Bool),
+            "I256" => Ok(AbiType::I256),
+            "U256" => Ok(AbiType::U256),
+            "ByteVec" => Ok(AbiType::ByteVec),
+            "Address" => Ok(AbiType::Address),
+            array => {
+                // The size follows the last `;`, nested arrays being written `[[U256;2];3]`
+                let inner = array.strip_prefix('[').and_then(|a| a.strip_suffix(']'));
+                let (element, size) =
+                    inner.and_then(|a| a.rsplit_once(';')).ok_or_else(unsupported)?;
+                let size = size.trim().parse().map_err(|_| unsupported())?;
+                Ok(AbiType::Array(Box::new(element.parse()?), size))
+            }
+        }
+    }
+}
+
+impl AbiType {
+    /// The number of node API values of this type. The node flattens arrays into their
+    /// elements, a `[U256;2]` being given as two `U256` values.
+    pub fn flattened_len(&self) -> usize {
+        match self {
+            AbiType::Array(element, size) => element.flattened_len() * size,
+            _ => 1,
+        }
+    }
+}
+
+/// A decoded field value. Numbers are kept as `BigDecimal`, like the amounts stored in the DB.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum AbiValue {
+    Bool(bool),
+    I256(BigDecimal),
+    U256(BigDecimal),
+    ByteVec(String),
+    Address(String),
+    Array(Vec<AbiValue>),
+}
+
+impl AbiValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AbiValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of an `I256` or `U256`.
+    pub fn as_number(&self) -> Option<&BigDecimal> {
+        match self {
+            AbiValue::I256(value) | AbiValue::U256(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The hex string of a `ByteVec` or the base58 string of an `Address`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AbiValue::ByteVec(value) | AbiValue::Address(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[AbiValue]> {
+        match self {
+            AbiValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// The signature of an event, as found in the `eventsSig` of an artifact.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSig {
+    pub name: String,
+    pub field_names: Vec<String>,
+    pub field_types: Vec<String>,
+}
+
+/// The parts of a Ralph contract artifact needed to decode its events.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Artifact {
+    name: String,
+    code_hash: Option<String>,
+    #[serde(default)]
+    events_sig: Vec<EventSig>,
+}
+
+/// An event signature with parsed field types.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventAbi {
+    pub name: String,
+    pub fields: Vec<(String, AbiType)>,
+}
+
+impl EventAbi {
+    /// The number of fields of the event as given by the node, arrays being flattened.
+    pub fn flattened_len(&self) -> usize {
+        self.fields.iter().map(|(_, abi_type)| abi_type.flattened_len()).sum()
+    }
+}
+
+/// The events of a contract, loaded from its compiled Ralph artifact (`*.ral.json`).
+///
+/// The node only gives the index and positional fields of an event. The `eventsSig` of the
+/// artifact lists the name, field names and field types of every event of the contract, in
+/// event index order, which is enough to decode events into named and typed fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractAbi {
+    pub name: String,
+    /// The hash of the contract code, which the node also gives for deployed contracts.
+    pub code_hash: Option<String>,
+    pub events: Vec<EventAbi>,
+}
+
+/// An event decoded with the ABI of its contract.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedEvent {
+    pub tx_id: String,
+    pub contract_address: String,
+    pub event_index: i32,
+    pub name: String,
+    pub fields: Vec<(String, AbiValue)>,
+}
+
+impl DecodedEvent {
+    /// The value of a field, by name.
+    pub fn field(&self, name: &str) -> Option<&AbiValue> {
+        self.fields.iter().find(|(field_name, _)| field_name == name).map(|(_, value)| value)
+    }
+
+    /// The fields by name.
+    pub fn field_map(&self) -> HashMap<&str, &AbiValue> {
+        self.fields.iter().map(|(name, value)| (name.as_str(), value)).collect()
+    }
+}
+
+impl ContractAbi {
+    /// Loads the ABI from the JSON of a contract artifact.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let artifact: Artifact = serde_json::from_str(json)?;
+        let events = artifact
+            .events_sig
+            .into_iter()
+            .map(|sig| {
+                if sig.field_names.len() != sig.field_types.len() {
+                    return Err(AbiError::FieldCountMismatch {
+                        event: sig.name,
+                        names: sig.field_names.len(),
+                        types: sig.field_types.len(),
+                    });
+                }
+                let fields = sig
+                    .field_names
+                    .into_iter()
+                    .zip(sig.field_types.iter())
+                    .map(|(name, field_type)| Ok((name, field_type.parse()?)))
+                    .collect::<Result<_>>()?;
+                Ok(EventAbi { name: sig.name, fields })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { name: artifact.name, code_hash: artifact.code_hash, events })
+    }
+
+    /// Loads the ABI from a contract artifact file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// The event with an index, system events having negative indexes.
+    pub fn event(&self, event_index: i32) -> Option<&EventAbi> {
+        usize::try_from(event_index).ok().and_then(|index| self.events.get(index))
+    }
+
+    /// The index of an event, by name.
+    pub fn event_index(&self, name: &str) -> Option<i32> {
+        self.events.iter().position(|event| event.name == name).map(|index| index as i32)
+    }
+
+    /// Decodes an event emitted by the contract into its named and typed fields. Array fields
+    /// are rebuilt from the consecutive values the node flattens them into.
+    pub fn decode_event(&self, event: &ContractEventByBlockHash) -> Result<DecodedEvent> {
+        let abi = self.event(event.event_index).ok_or(AbiError::UnknownEvent(event.event_index))?;
+        if abi.flattened_len() != event.fields.len() {
+            return Err(AbiError::FieldCount {
+                event: abi.name.clone(),
+                expected: abi.flattened_len(),
+                found: event.fields.len(),
+            });
+        }
+        let mut values = event.fields.iter();
+        let fields = abi
+            .fields
+            .iter()
+            .map(|(name, abi_type)| {
+                Ok((name.clone(), decode_value(&abi.name, name, abi_type, &mut values)?))
+            })
+            .collect::<Result<_>>()?;
+        Ok(DecodedEvent {
+            tx_id: event.tx_id.clone(),
+            contract_address: event.contract_address.clone(),
+            event_index: event.event_index,
+            name: abi.name.clone(),
+            fields,
+        })
+    }
+}
+
+/// Decodes a value of `abi_type`, consuming as many fields as its flattened length.
+fn decode_value<'a>(
+    event: &str,
+    name: &str,
+    abi_type: &AbiType,
+    fields: &mut impl Iterator<Item = &'a EventField>,
+) -> Result<AbiValue> {
+    if let AbiType::Array(element, size) = abi_type {
+        return Ok(AbiValue::Array(
+            (0..*size)
+                .map(|_| decode_value(event, name, element, fields))
+                .collect::<Result<_>>()?,
+        ));
+    }
+    let field = fields.next().expect("the field count is checked before decoding");
+    let number = || BigDecimal::from_str(field.value.as_str()?).ok();
+    let text = || field.value.as_str().map(String::from);
+    let (expected, value) = match abi_type {
+        AbiType::Bool => (EventFieldType::Bool, field.value.as_bool().map(AbiValue::Bool)),
+        AbiType::I256 => (EventFieldType::I256, number().map(AbiValue::I256)),
+        AbiType::U256 => (EventFieldType::U256, number().map(AbiValue::U256)),
+        AbiType::ByteVec => (EventFieldType::ByteVec, text().map(AbiValue::ByteVec)),
+        AbiType::Address => (EventFieldType::Address, text().map(AbiValue::Address)),
+        AbiType::Array(..) => unreachable!("arrays are decoded element by element"),
+    };
+    if field.field_type != expected {
+        return Err(AbiError::FieldType {
+            event: event.to_string(),
+            field: name.to_string(),
+            expected,
+            found: field.field_type.clone(),
+        });
+    }
+    value.ok_or_else(|| AbiError::InvalidValue {
+        event: event.to_string(),
+        field: name.to_string(),
+        value: field.value_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PAIR_ARTIFACT: &str = r#"{
+        "version": "v3.1.0",
+        "name": "TokenPair",
+        "bytecode": "00",
+        "codeHash": "4b0a5f1ba4d2c5fd0e2d8a6ae6c3d3ac1ebf0ac2a6c3b8b5a5e6e91f4c2c2a1b",
+        "fieldsSig": { "names": ["token0Id"], "types": ["ByteVec"], "isMutable": [false] },
+        "eventsSig": [
+            {
+                "name": "Mint",
+                "fieldNames": ["sender", "amount0", "amount1", "liquidity"],
+                "fieldTypes": ["Address", "U256", "U256", "U256"]
+            },
+            {
+                "name": "Oracle",
+                "fieldNames": ["prices", "valid", "delta"],
+                "fieldTypes": ["[U256;2]", "Bool", "I256"]
+            },
+            {
+                "name": "Votes",
+                "fieldNames": ["voter", "ballots"],
+                "fieldTypes": ["Address", "[[Bool;2];2]"]
+            }
+        ],
+        "functions": [],
+        "constants": [],
+        "enums": []
+    }"#;
+
+    fn event(event_index: i32, fields: serde_json::Value) -> ContractEventByBlockHash {
+        serde_json::from_value(json!({
+            "txId": "tx",
+            "contractAddress": "pair",
+            "eventIndex": event_index,
+            "fields": fields
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_abi_type() {
+        assert_eq!("U256".parse::<AbiType>().unwrap(), AbiType::U256);
+        let nested = "[[Bool;2];3]".parse::<AbiType>().unwrap();
+        assert_eq!(nested, AbiType::Array(Box::new(AbiType::Array(Box::new(AbiType::Bool), 2)), 3));
+        assert_eq!(nested.flattened_len(), 6);
+        assert!("Foo".parse::<AbiType>().is_err());
+        assert!("[U256;n]".parse::<AbiType>().is_err());
+    }
+
+    #[test]
+    fn test_decode_event() {
+        let abi = ContractAbi::from_json(PAIR_ARTIFACT).unwrap();
+        assert_eq!(abi.name, "TokenPair");
+        assert_eq!(
+            abi.code_hash.as_deref(),
+            Some("4b0a5f1ba4d2c5fd0e2d8a6ae6c3d3ac1ebf0ac2a6c3b8b5a5e6e91f4c2c2a1b")
+        );
+        assert_eq!(abi.event_index("Oracle"), Some(1));
+
+        let mint = abi
+            .decode_event(&event(
+                0,
+                json!([
+                    { "type": "Address", "value": "1FQuaJLe6BAcHYoQdhW2TMENVpRgGS8rpMtLRsy9ZjbPq" },
+                    { "type": "U256", "value": "10000" },
+                    { "type": "U256", "value": "22000" },
+                    { "type": "U256", "value": "14832" }
+                ]),
+            ))
+            .unwrap();
+        assert_eq!(mint.name, "Mint");
+        assert_eq!(
+            mint.field("sender").and_then(AbiValue::as_str),
+            Some("1FQuaJLe6BAcHYoQdhW2TMENVpRgGS8rpMtLRsy9ZjbPq")
+        );
+        assert_eq!(mint.field("liquidity").and_then(AbiValue::as_number), Some(&14832.into()));
+
+        // The node flattens arrays into their elements
+        let oracle = abi
+            .decode_event(&event(
+                1,
+                json!([
+                    { "type": "U256", "value": "1" },
+                    { "type": "U256", "value": "2" },
+                    { "type": "Bool", "value": true },
+                    { "type": "I256", "value": "-5" }
+                ]),
+            ))
+            .unwrap();
+        let field_map = oracle.field_map();
+        assert_eq!(
+            field_map["prices"],
+            &AbiValue::Array(vec![AbiValue::U256(1.into()), AbiValue::U256(2.into())])
+        );
+        assert_eq!(field_map["valid"].as_bool(), Some(true));
+        assert_eq!(field_map["delta"].as_number(), Some(&(-5).into()));
+
+        let bool_field = |value: bool| json!({ "type": "Bool", "value": value });
+        let votes = abi
+            .decode_event(&event(
+                2,
+                json!([
+                    { "type": "Address", "value": "1FQuaJLe6BAcHYoQdhW2TMENVpRgGS8rpMtLRsy9ZjbPq" },
+                    bool_field(true),
+                    bool_field(false),
+                    bool_field(false),
+                    bool_field(true)
+                ]),
+            ))
+            .unwrap();
+        let ballot = |a, b| AbiValue::Array(vec![AbiValue::Bool(a), AbiValue::Bool(b)]);
+        assert_eq!(
+            votes.field("ballots"),
+            Some(&AbiValue::Array(vec![ballot(true, false), ballot(false, true)]))
+        );
+
+        // System events and events not matching their signature
+        assert!(matches!(abi.decode_event(&event(-1, json!([]))), Err(AbiError::UnknownEvent(-1))));
+        assert!(matches!(
+            abi.decode_event(&event(0, json!([{ "type": "U256", "value": "1" }]))),
+            Err(AbiError::FieldCount { expected: 4, found: 1, .. })
+        ));
+        assert!(matches!(
+            abi.decode_event(&event(
+                1,
+                json!([
+                    { "type": "U256", "value": "1" },
+                    { "type": "Bool", "value": true },
+                    { "type": "I256", "value": "-5" }
+                ])
+            )),
+            Err(AbiError::FieldCount { expected: 4, found: 3, .. })
+        ));
+        assert!(matches!(
+            abi.decode_event(&event(
+                1,
+                json!([
+                    { "type": "U256", "value": "1" },
+                    { "type": "Bool", "value": true },
+                    { "type": "Bool", "value": true },
+                    { "type": "I256", "value": "-5" }
+                ])
+            )),
+            Err(AbiError::FieldType {
+                expected: EventFieldType::U256,
+                found: EventFieldType::Bool,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_artifact() {
+        let artifact = json!({
+            "name": "Broken",
+            "eventsSig": [{ "name": "E", "fieldNames": ["a"], "fieldTypes": [] }]
+        });
+        assert!(matches!(
+            ContractAbi::from_json(&artifact.to_string()),
+            Err(AbiError::FieldCountMismatch { names: 1, types: 0, .. })
+        ));
+        assert!(matches!(ContractAbi::from_json("{}"), Err(AbiError::Json(_))));
+    }
+}
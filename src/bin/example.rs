@@ -1,8 +1,10 @@
 use bento_alephium::{
     client::Network,
     config::ProcessorConfig,
+    utils::wait_for_signal,
     worker::{SyncOptions, Worker},
 };
+use tokio_util::sync::CancellationToken;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -24,13 +26,31 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         None,
         Some(SyncOptions {
             start_ts: Some(1716560632750),
+            end_ts: None,
             step: Some(1000),
             back_step: None,
             sync_duration: None,
+            ws_url: None,
+            backfill_concurrency: None,
+            target_block_count: None,
+            min_step: None,
+            max_step: None,
+            max_parent_fetch_depth: None,
+            group_num: None,
+            reorg_timeout: None,
+            finality: Default::default(),
         }),
     )
     .await?;
 
-    worker.run().await;
+    // Stop gracefully on SIGINT/SIGTERM
+    let shutdown = CancellationToken::new();
+    tokio::spawn(wait_for_signal(shutdown.clone()));
+
+    let reason = worker.run(shutdown).await?;
+    tracing::info!(reason = ?reason, "Worker stopped");
+
+    // Close the database pool before exiting
+    drop(worker);
     Ok(())
 }
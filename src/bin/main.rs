@@ -1,14 +1,32 @@
+use bento_alephium::utils::wait_for_signal;
+use tokio_util::sync::CancellationToken;
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    // Setup logger
+    tracing_subscriber::fmt().init();
+
     println!("Starting server...");
-    println!("Server is ready and running on http://0.0.0.0:8080");
 
-    actix_web::HttpServer::new(|| {
+    // Signals are handled below, so that the server stops like the workers do
+    let server = actix_web::HttpServer::new(|| {
         actix_web::App::new().route("/", actix_web::web::get().to(|| async { "Hello!" }))
     })
+    .disable_signals()
     .bind("0.0.0.0:8080")?
-    .run()
-    .await?;
+    .run();
+    println!("Server is ready and running on http://0.0.0.0:8080");
+
+    // Stop gracefully on SIGINT/SIGTERM, finishing the in-flight requests
+    let shutdown = CancellationToken::new();
+    tokio::spawn(wait_for_signal(shutdown.clone()));
+    let handle = server.handle();
+    tokio::spawn(async move {
+        shutdown.cancelled().await;
+        handle.stop(true).await;
+    });
 
+    server.await?;
+    tracing::info!("Server stopped");
     Ok(())
 }
@@ -1,11 +1,161 @@
 use crate::types::{
-    BlockAndEvents, BlockEntry, BlockHeaderEntry, BlocksAndEventsPerTimestampRange,
-    BlocksPerTimestampRange, Transaction,
+    Balance, BlockAndEvents, BlockEntry, BlockHeaderEntry, BlocksAndEventsPerTimestampRange,
+    BlocksPerTimestampRange, CallContract, CallContractResult, ChainInfo, ContractEvents,
+    ContractEventsByTxId, ContractState, HashesAtHeight, MempoolTransactions,
+    MultipleCallContractResult, NodeInfo, NodeVersion, SelfClique, Transaction, TxStatus, Utxos,
+};
+use crate::utils::{address_from_contract_id, group_of_contract_id};
+use futures::future::join_all;
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, Method, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    env,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
 };
-use anyhow::Result;
-use std::env;
 use url::Url;
 
+pub type Result<T, E = ClientError> = std::result::Result<T, E>;
+
+/// Errors returned by the node client.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The request could not be built.
+    #[error("Invalid url: {0}")]
+    Url(#[from] url::ParseError),
+    /// The client was created without any node to send requests to.
+    #[error("No node URL configured")]
+    NoNodeUrl,
+    /// The node could not be reached (connection refused, reset, DNS failure, ...).
+    #[error("Network error: {0}")]
+    Network(#[source] reqwest::Error),
+    /// The request did not complete within the configured timeout.
+    #[error("Request timed out after {0:?}")]
+    Timeout(Duration),
+    /// The node answered with a non-success status and an `{"detail": ...}` error payload.
+    #[error("Node API error ({status}): {detail}")]
+    Api { status: StatusCode, detail: String, retry_after: Option<Duration> },
+    /// The node answered with a non-success status and a body that is not an API error payload.
+    #[error("HTTP error ({status}): {body}")]
+    Status { status: StatusCode, body: String, retry_after: Option<Duration> },
+    /// The response body could not be decoded into the expected type.
+    #[error("Failed to decode response: {0}")]
+    Decode(#[source] serde_json::Error),
+}
+
+impl ClientError {
+    /// Returns the HTTP status of the response, if the node answered at all.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Api { status, .. } | ClientError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Returns the delay requested by the node through the `Retry-After` header.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ClientError::Api { retry_after, .. } | ClientError::Status { retry_after, .. } => {
+                *retry_after
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the request may succeed if sent again.
+    ///
+    /// Network failures, timeouts, `429 Too Many Requests` and `5xx` responses are transient;
+    /// client errors and decoding failures are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ClientError::Network(_) | ClientError::Timeout(_) => true,
+            ClientError::Api { status, .. } | ClientError::Status { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            ClientError::Url(_) | ClientError::NoNodeUrl | ClientError::Decode(_) => false,
+        }
+    }
+}
+
+/// Error payload returned by the node for failed requests.
+#[derive(Deserialize, Debug)]
+struct ApiErrorBody {
+    detail: String,
+}
+
+/// Builds the error for a non-success response from its status, `Retry-After` header and body.
+fn error_from_response(
+    status: StatusCode,
+    retry_after: Option<Duration>,
+    body: String,
+) -> ClientError {
+    match serde_json::from_str::<ApiErrorBody>(&body) {
+        Ok(api_error) => ClientError::Api { status, detail: api_error.detail, retry_after },
+        Err(_) => ClientError::Status { status, body, retry_after },
+    }
+}
+
+/// Parses a `Retry-After` header value expressed in seconds.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// Retry and timeout settings applied to every request made by the [`Client`].
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Timeout of a single attempt.
+    pub timeout: Duration,
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between two attempts.
+    pub max_backoff: Duration,
+    /// Upper bound of the delay requested by the node through `Retry-After`, which may exceed
+    /// `max_backoff`.
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            max_retry_after: Duration::from_secs(120),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that sends each request exactly once.
+    pub fn no_retry() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    /// Returns the delay to wait after the given (1-based) failed attempt.
+    ///
+    /// The delay doubles with every attempt up to `max_backoff`, and is jittered uniformly
+    /// in `[delay / 2, delay]` so that several workers do not retry in lockstep.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(31);
+        let delay = self.initial_backoff.saturating_mul(1 << exp).min(self.max_backoff);
+        let half = delay / 2;
+        let jitter_ms = rand::thread_rng().gen_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter_ms)
+    }
+
+    /// Returns the delay to wait after a failed attempt, honouring `Retry-After` up to
+    /// `max_retry_after` when present.
+    pub fn delay_for(&self, attempt: u32, err: &ClientError) -> Duration {
+        err.retry_after()
+            .map(|d| d.min(self.max_retry_after))
+            .unwrap_or_else(|| self.backoff(attempt))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Network {
     Development,
@@ -15,7 +165,7 @@ pub enum Network {
 }
 
 impl Network {
-    /// Returns the base URL for the network.
+    /// Returns the base URL for the network, or the first one if several nodes are configured.
     ///
     /// # Arguments
     ///
@@ -25,6 +175,26 @@ impl Network {
     ///
     /// A string containing the base URL of the network.
     pub fn base_url(&self) -> String {
+        self.base_urls().into_iter().next().unwrap_or_default()
+    }
+
+    /// Returns the base URLs of all the nodes configured for the network.
+    ///
+    /// The node URL (either from the environment or `Network::Custom`) may contain several
+    /// comma-separated URLs, in which case the client fails over between them.
+    ///
+    /// # Returns
+    ///
+    /// A list of base URLs, in configuration order.
+    pub fn base_urls(&self) -> Vec<String> {
+        self.raw_url()
+            .split(',')
+            .map(|url| url.trim().trim_end_matches('/').to_owned())
+            .filter(|url| !url.is_empty())
+            .collect()
+    }
+
+    fn raw_url(&self) -> String {
         match self {
             Network::Development => {
                 env::var("DEV_NODE_URL").unwrap_or_else(|_| "http://127.0.0.1:12973".to_owned())
@@ -51,11 +221,139 @@ impl Default for Network {
     }
 }
 
+/// Settings controlling how the [`Client`] spreads requests across several nodes.
+#[derive(Clone, Debug)]
+pub struct FailoverPolicy {
+    /// Minimum delay between two health checks of the configured nodes.
+    pub health_check_interval: Duration,
+    /// Number of blocks a node may lag behind the most up-to-date one before it is skipped.
+    pub max_height_lag: i64,
+}
+
+impl Default for FailoverPolicy {
+    fn default() -> Self {
+        Self { health_check_interval: Duration::from_secs(30), max_height_lag: 3 }
+    }
+}
+
+/// Health of a single node, as last observed by the client.
+#[derive(Clone, Debug)]
+pub struct EndpointStatus {
+    pub base_url: String,
+    pub healthy: bool,
+    pub height: Option<i64>, // Height of chain (0, 0) at the last health check.
+    pub consecutive_failures: u32,
+}
+
+impl EndpointStatus {
+    fn new(base_url: String) -> Self {
+        Self { base_url, healthy: true, height: None, consecutive_failures: 0 }
+    }
+}
+
+/// The nodes known to a client, shared between its clones.
+#[derive(Debug)]
+struct Endpoints {
+    statuses: RwLock<Vec<EndpointStatus>>,
+    last_health_check: Mutex<Option<Instant>>,
+}
+
+impl Endpoints {
+    fn new(base_urls: Vec<String>) -> Self {
+        Self {
+            statuses: RwLock::new(base_urls.into_iter().map(EndpointStatus::new).collect()),
+            last_health_check: Mutex::new(None),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.statuses.read().unwrap().len()
+    }
+
+    fn snapshot(&self) -> Vec<EndpointStatus> {
+        self.statuses.read().unwrap().clone()
+    }
+
+    fn base_url(&self, index: usize) -> String {
+        self.statuses.read().unwrap()[index].base_url.clone()
+    }
+
+    /// Picks the endpoint to send the next request to.
+    fn select(&self) -> usize {
+        select_endpoint(&self.statuses.read().unwrap())
+    }
+
+    fn record_success(&self, index: usize) {
+        let mut statuses = self.statuses.write().unwrap();
+        statuses[index].healthy = true;
+        statuses[index].consecutive_failures = 0;
+    }
+
+    fn record_failure(&self, index: usize) {
+        let mut statuses = self.statuses.write().unwrap();
+        statuses[index].healthy = false;
+        statuses[index].consecutive_failures += 1;
+    }
+
+    /// Returns true (and starts a new period) if a health check is due.
+    fn health_check_due(&self, interval: Duration) -> bool {
+        let mut last = self.last_health_check.lock().unwrap();
+        match *last {
+            Some(at) if at.elapsed() < interval => false,
+            _ => {
+                *last = Some(Instant::now());
+                true
+            }
+        }
+    }
+
+    /// Applies the results of a health check, marking lagging nodes as unhealthy.
+    fn apply_health_check(&self, results: Vec<Option<i64>>, max_height_lag: i64) {
+        let mut statuses = self.statuses.write().unwrap();
+        let best_height = results.iter().flatten().max().copied();
+        for (status, height) in statuses.iter_mut().zip(results) {
+            status.height = height;
+            status.healthy = match (height, best_height) {
+                (Some(height), Some(best)) => best - height <= max_height_lag,
+                _ => false,
+            };
+            if status.healthy {
+                status.consecutive_failures = 0;
+            }
+        }
+    }
+}
+
+/// Selects the healthy endpoint with the highest known height, preferring configuration order on
+/// ties. When no endpoint is healthy, the one with the fewest consecutive failures is used so
+/// requests keep probing the nodes instead of failing outright.
+fn select_endpoint(statuses: &[EndpointStatus]) -> usize {
+    let healthy = statuses
+        .iter()
+        .enumerate()
+        .filter(|(_, status)| status.healthy)
+        .max_by(|(i, a), (j, b)| a.height.cmp(&b.height).then(j.cmp(i)));
+    match healthy {
+        Some((index, _)) => index,
+        None => statuses
+            .iter()
+            .enumerate()
+            .min_by_key(|(i, status)| (status.consecutive_failures, *i))
+            .map(|(index, _)| index)
+            .unwrap_or(0),
+    }
+}
+
 /// Struct representing a client that interacts with the Alephium node network.
+///
+/// The client may be configured with several nodes: requests are routed to the healthiest,
+/// most up-to-date one and fail over to the others when it errors or lags behind.
 #[derive(Clone, Debug)]
 pub struct Client {
-    inner: reqwest::Client, // The inner HTTP client used for requests.
-    base_url: String,       // The base URL for making requests to the node network.
+    inner: reqwest::Client,          // The inner HTTP client used for requests.
+    endpoints: Arc<Endpoints>,       // The nodes requests are routed to.
+    retry_policy: RetryPolicy,       // The retry and timeout settings applied to every request.
+    failover_policy: FailoverPolicy, // The health check settings used to route requests.
 }
 
 impl Client {
@@ -67,18 +365,217 @@ impl Client {
     ///
     /// # Returns
     ///
-    /// A new `Client` instance.
-    pub fn new(network: Network) -> Self {
-        Self { inner: reqwest::Client::new(), base_url: network.base_url() }
+    /// A new `Client` instance, or a `ClientError` if no valid node URL is configured.
+    pub fn new(network: Network) -> Result<Self> {
+        Self::new_with_retry_policy(network, RetryPolicy::default())
+    }
+
+    /// Creates a new `Client` instance with a custom retry policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The network to connect to.
+    /// * `retry_policy` - The retry and timeout settings applied to every request.
+    ///
+    /// # Returns
+    ///
+    /// A new `Client` instance, or a `ClientError` if no valid node URL is configured.
+    pub fn new_with_retry_policy(network: Network, retry_policy: RetryPolicy) -> Result<Self> {
+        Self::new_with_endpoints(network.base_urls(), retry_policy, FailoverPolicy::default())
+    }
+
+    /// Creates a new `Client` instance load balancing over several nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_urls` - The base URLs of the nodes to route requests to.
+    /// * `retry_policy` - The retry and timeout settings applied to every request.
+    /// * `failover_policy` - The health check settings used to route requests.
+    ///
+    /// # Returns
+    ///
+    /// A new `Client` instance, or a `ClientError` if `base_urls` is empty or holds an invalid
+    /// URL.
+    pub fn new_with_endpoints(
+        base_urls: Vec<String>,
+        retry_policy: RetryPolicy,
+        failover_policy: FailoverPolicy,
+    ) -> Result<Self> {
+        if base_urls.is_empty() {
+            return Err(ClientError::NoNodeUrl);
+        }
+        for base_url in &base_urls {
+            Url::parse(base_url)?;
+        }
+        Ok(Self {
+            inner: reqwest::Client::new(),
+            endpoints: Arc::new(Endpoints::new(base_urls)),
+            retry_policy,
+            failover_policy,
+        })
+    }
+
+    /// Returns the retry policy used by this client.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Returns the last observed status of every configured node.
+    pub fn endpoints(&self) -> Vec<EndpointStatus> {
+        self.endpoints.snapshot()
+    }
+
+    /// Checks every configured node through `/infos/self-clique` and `/blockflow/chain-info`.
+    ///
+    /// Nodes that are unreachable, not synced, or lagging more than `max_height_lag` blocks
+    /// behind the most up-to-date one are skipped until they recover.
+    pub async fn check_health(&self) {
+        let count = self.endpoints.len();
+        let results = join_all((0..count).map(|index| self.probe(index))).await;
+        for (status, height) in self.endpoints.snapshot().iter().zip(results.iter()) {
+            if height.is_none() {
+                tracing::warn!(node = status.base_url, "Node is unhealthy");
+            }
+        }
+        self.endpoints.apply_health_check(results, self.failover_policy.max_height_lag);
+    }
+
+    /// Returns the height of chain (0, 0) on the given node, or `None` if it is not usable.
+    async fn probe(&self, index: usize) -> Option<i64> {
+        let base_url = self.endpoints.base_url(index);
+        let clique: SelfClique = self
+            .get_once(Url::parse(&format!("{}/infos/self-clique", base_url)).ok()?)
+            .await
+            .ok()?;
+        if !clique.self_ready || !clique.synced {
+            return None;
+        }
+        let chain_info: ChainInfo = self
+            .get_once(
+                Url::parse(&format!("{}/blockflow/chain-info?fromGroup=0&toGroup=0", base_url))
+                    .ok()?,
+            )
+            .await
+            .ok()?;
+        Some(chain_info.current_height)
+    }
+
+    /// Sends a GET request to the given endpoint, retrying transient failures according to
+    /// the retry policy, and decodes the JSON response.
+    async fn get<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        self.request(Method::GET, endpoint, None::<&()>).await
+    }
+
+    /// Sends a POST request with a JSON body to the given endpoint, retrying transient failures
+    /// according to the retry policy, and decodes the JSON response. Only meant for endpoints
+    /// without side effects, which can safely be called more than once.
+    async fn post<T: DeserializeOwned, B: Serialize>(&self, endpoint: &str, body: &B) -> Result<T> {
+        self.request(Method::POST, endpoint, Some(body)).await
+    }
+
+    /// Sends a request to the given endpoint, retrying transient failures according to the
+    /// retry policy, and decodes the JSON response.
+    ///
+    /// Each attempt is routed to the best available node; a failed node is skipped by the next
+    /// attempt. Every node is tried once before giving up, whatever `max_attempts`: failing
+    /// over to a node not tried yet happens right away and doesn't count as a retry.
+    async fn request<T: DeserializeOwned, B: Serialize>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Option<&B>,
+    ) -> Result<T> {
+        if self.endpoints.len() > 1
+            && self.endpoints.health_check_due(self.failover_policy.health_check_interval)
+        {
+            self.check_health().await;
+        }
+
+        let mut attempt = 1;
+        let mut index = self.endpoints.select();
+        let mut tried = vec![false; self.endpoints.len()];
+        loop {
+            tried[index] = true;
+            let url = Url::parse(&format!("{}/{}", self.endpoints.base_url(index), endpoint))?;
+            let err = match self.send_once(method.clone(), url, body).await {
+                Ok(response) => {
+                    self.endpoints.record_success(index);
+                    return Ok(response);
+                }
+                Err(err) => err,
+            };
+            if !err.is_retryable() {
+                return Err(err);
+            }
+            self.endpoints.record_failure(index);
+
+            let next = self.endpoints.select();
+            let delay = if !tried[next] {
+                Duration::ZERO
+            } else if attempt < self.retry_policy.max_attempts {
+                attempt += 1;
+                self.retry_policy.delay_for(attempt - 1, &err)
+            } else {
+                return Err(err);
+            };
+            tracing::warn!(
+                endpoint = endpoint,
+                node = self.endpoints.base_url(index),
+                attempt = attempt,
+                error = %err,
+                "Request failed, retrying in {:?}",
+                delay
+            );
+            tokio::time::sleep(delay).await;
+            index = next;
+        }
+    }
+
+    /// Sends a single GET request and decodes the JSON response.
+    async fn get_once<T: DeserializeOwned>(&self, url: Url) -> Result<T> {
+        self.send_once(Method::GET, url, None::<&()>).await
+    }
+
+    /// Sends a single request and decodes the JSON response.
+    async fn send_once<T: DeserializeOwned, B: Serialize>(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<&B>,
+    ) -> Result<T> {
+        let timeout = self.retry_policy.timeout;
+        let to_client_error = |err: reqwest::Error| {
+            if err.is_timeout() {
+                ClientError::Timeout(timeout)
+            } else {
+                ClientError::Network(err)
+            }
+        };
+
+        let mut request = self.inner.request(method, url).timeout(timeout);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send().await.map_err(to_client_error)?;
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = response.text().await.map_err(to_client_error)?;
+
+        if !status.is_success() {
+            return Err(error_from_response(status, retry_after, body));
+        }
+        serde_json::from_str(&body).map_err(ClientError::Decode)
     }
 
     // List blocks on the given time interval.
     // GET:/blockflow/blocks?fromTs={from_ts}&toTs={to_ts}
     pub async fn get_blocks(&self, from_ts: u128, to_ts: u128) -> Result<BlocksPerTimestampRange> {
         let endpoint = format!("blockflow/blocks?fromTs={}&toTs={}", from_ts, to_ts);
-        let url = Url::parse(&format!("{}/{}", self.base_url, endpoint))?;
-        let response = self.inner.get(url).send().await?.json().await?;
-        Ok(response)
+        self.get(&endpoint).await
     }
 
     /// List blocks with events on the given time interval.
@@ -90,25 +587,21 @@ impl Client {
     ///
     /// # Returns
     ///
-    /// A `Result` containing a `BlocksAndEventsPerTimestampRange` structure, or an error if the request fails.
+    /// A `Result` containing a `BlocksAndEventsPerTimestampRange` structure, or a `ClientError` if the request fails.
     pub async fn get_blocks_and_events(
         &self,
         from_ts: i64,
         to_ts: i64,
     ) -> Result<BlocksAndEventsPerTimestampRange> {
         let endpoint = format!("blockflow/blocks-with-events?fromTs={}&toTs={}", from_ts, to_ts);
-        let url = Url::parse(&format!("{}/{}", self.base_url, endpoint))?;
-        let response = self.inner.get(url).send().await?.json().await?;
-        Ok(response)
+        self.get(&endpoint).await
     }
 
     // Get a block with hash.
     // GET:/blockflow/blocks/{block_hash}
     pub async fn get_block(&self, block_hash: &String) -> Result<BlockEntry> {
         let endpoint = format!("blockflow/blocks/{}", block_hash);
-        let url = Url::parse(&format!("{}/{}", self.base_url, endpoint))?;
-        let response = self.inner.get(url).send().await?.json().await?;
-        Ok(response)
+        self.get(&endpoint).await
     }
 
     /// Get a block with events by its hash.
@@ -119,24 +612,20 @@ impl Client {
     ///
     /// # Returns
     ///
-    /// A `Result` containing a `BlockAndEvents` structure, or an error if the request fails.
+    /// A `Result` containing a `BlockAndEvents` structure, or a `ClientError` if the request fails.
     pub async fn get_block_and_events_by_hash(
         &self,
         block_hash: &String,
     ) -> Result<BlockAndEvents> {
         let endpoint = format!("blockflow/blocks-with-events/{}", block_hash);
-        let url = Url::parse(&format!("{}/{}", self.base_url, endpoint))?;
-        let response = self.inner.get(url).send().await?.json().await?;
-        Ok(response)
+        self.get(&endpoint).await
     }
 
     // Get block header.
     // GET:/blockflow/headers/{block_hash}
     pub async fn get_block_header(&self, block_hash: &String) -> Result<BlockHeaderEntry> {
         let endpoint = format!("blockflow/headers/{}", block_hash);
-        let url = Url::parse(&format!("{}/{}", self.base_url, endpoint))?;
-        let response = self.inner.get(url).send().await?.json().await?;
-        Ok(response)
+        self.get(&endpoint).await
     }
 
     /// Get transaction details by transaction ID.
@@ -147,11 +636,432 @@ impl Client {
     ///
     /// # Returns
     ///
-    /// A `Result` containing a `Transaction` structure, or an error if the request fails.
+    /// A `Result` containing a `Transaction` structure, or a `ClientError` if the request fails.
     pub async fn get_transaction(&self, tx_id: &str) -> Result<Transaction> {
         let endpoint = format!("transactions/details/{}", tx_id);
-        let url = Url::parse(&format!("{}/{}", self.base_url, endpoint))?;
-        let response = self.inner.get(url).send().await?.json().await?;
-        Ok(response)
+        self.get(&endpoint).await
+    }
+
+    /// Get the current height of a chain index.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_group` - The source group of the chain.
+    /// * `to_group` - The destination group of the chain.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `ChainInfo` structure, or a `ClientError` if the request fails.
+    pub async fn get_chain_info(&self, from_group: i64, to_group: i64) -> Result<ChainInfo> {
+        let endpoint =
+            format!("blockflow/chain-info?fromGroup={}&toGroup={}", from_group, to_group);
+        self.get(&endpoint).await
+    }
+
+    /// Get the block hashes of a chain index at a given height.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_group` - The source group of the chain.
+    /// * `to_group` - The destination group of the chain.
+    /// * `height` - The height of the blocks.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `HashesAtHeight` structure, or a `ClientError` if the request fails.
+    pub async fn get_hashes_at_height(
+        &self,
+        from_group: i64,
+        to_group: i64,
+        height: i64,
+    ) -> Result<HashesAtHeight> {
+        let endpoint = format!(
+            "blockflow/hashes?fromGroup={}&toGroup={}&height={}",
+            from_group, to_group, height
+        );
+        self.get(&endpoint).await
+    }
+
+    /// Check if a block is part of the main chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - The hash of the block to check.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing whether the block is in the main chain, or a `ClientError` if the request fails.
+    pub async fn is_block_in_main_chain(&self, block_hash: &str) -> Result<bool> {
+        let endpoint = format!("blockflow/is-block-in-main-chain?blockHash={}", block_hash);
+        self.get(&endpoint).await
+    }
+
+    // Get info about the clique of the node.
+    // GET:/infos/self-clique
+    pub async fn get_self_clique(&self) -> Result<SelfClique> {
+        self.get("infos/self-clique").await
+    }
+
+    // Get info about the node.
+    // GET:/infos/node
+    pub async fn get_node_info(&self) -> Result<NodeInfo> {
+        self.get("infos/node").await
+    }
+
+    // Get the version of the node.
+    // GET:/infos/version
+    pub async fn get_node_version(&self) -> Result<NodeVersion> {
+        self.get("infos/version").await
+    }
+
+    /// Get the balance of an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address to get the balance of.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `Balance` structure, or a `ClientError` if the request fails.
+    pub async fn get_address_balance(&self, address: &str) -> Result<Balance> {
+        let endpoint = format!("addresses/{}/balance", address);
+        self.get(&endpoint).await
+    }
+
+    /// Get the unspent outputs of an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address to get the UTXOs of.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `Utxos` structure, or a `ClientError` if the request fails.
+    pub async fn get_address_utxos(&self, address: &str) -> Result<Utxos> {
+        let endpoint = format!("addresses/{}/utxos", address);
+        self.get(&endpoint).await
+    }
+
+    /// Get the state of a contract.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the contract.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `ContractState` structure, or a `ClientError` if the request fails.
+    pub async fn get_contract_state(&self, address: &str) -> Result<ContractState> {
+        let endpoint = format!("contracts/{}/state", address);
+        self.get(&endpoint).await
+    }
+
+    /// Get a page of the events emitted by a contract.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the contract.
+    /// * `start` - The counter of the first event to return.
+    /// * `limit` - The maximum number of events to return, defaults to the node's page size.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `ContractEvents` structure, or a `ClientError` if the request fails.
+    pub async fn get_contract_events(
+        &self,
+        address: &str,
+        start: i32,
+        limit: Option<i32>,
+    ) -> Result<ContractEvents> {
+        let mut endpoint = format!("events/contract/{}?start={}", address, start);
+        if let Some(limit) = limit {
+            endpoint.push_str(&format!("&limit={}", limit));
+        }
+        self.get(&endpoint).await
+    }
+
+    /// Get the events emitted by a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_id` - The ID of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `ContractEventsByTxId` structure, or a `ClientError` if the request fails.
+    pub async fn get_events_by_tx_id(&self, tx_id: &str) -> Result<ContractEventsByTxId> {
+        let endpoint = format!("events/tx-id/{}", tx_id);
+        self.get(&endpoint).await
+    }
+
+    /// Get the status of a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_id` - The ID of the transaction.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a `TxStatus`, or a `ClientError` if the request fails.
+    pub async fn get_transaction_status(&self, tx_id: &str) -> Result<TxStatus> {
+        let endpoint = format!("transactions/status?txId={}", tx_id);
+        self.get(&endpoint).await
+    }
+
+    /// Call contract methods without sending a transaction, in a single request.
+    ///
+    /// # Arguments
+    ///
+    /// * `calls` - The methods to call.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the outcome of each call in order, or a `ClientError` if the request fails.
+    pub async fn call_contracts(&self, calls: &[CallContract]) -> Result<Vec<CallContractResult>> {
+        #[derive(Serialize)]
+        struct MultipleCallContract<'a> {
+            calls: &'a [CallContract],
+        }
+        let result: MultipleCallContractResult =
+            self.post("contracts/multicall-contract", &MultipleCallContract { calls }).await?;
+        Ok(result.results)
+    }
+
+    /// Call methods without arguments of a contract, given its id, in a single request.
+    ///
+    /// # Arguments
+    ///
+    /// * `contract_id` - The id of the contract.
+    /// * `methods` - The indexes of the methods to call.
+    /// * `group_num` - The number of groups of the network.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the outcome of each call in order, no outcome when the node rejects
+    /// the calls, or a `ClientError` if the node can't be reached.
+    pub async fn call_contract_methods(
+        &self,
+        contract_id: &str,
+        methods: &[i32],
+        group_num: i64,
+    ) -> Result<Vec<CallContractResult>> {
+        let (Some(address), Some(group)) =
+            (address_from_contract_id(contract_id), group_of_contract_id(contract_id, group_num))
+        else {
+            tracing::warn!(contract_id = contract_id, "Invalid contract id, skipping calls");
+            return Ok(vec![]);
+        };
+        let calls: Vec<CallContract> = methods
+            .iter()
+            .map(|&method_index| CallContract {
+                group: group as i32,
+                address: address.clone(),
+                method_index,
+                args: vec![],
+            })
+            .collect();
+        match self.call_contracts(&calls).await {
+            Ok(results) => Ok(results),
+            // The node rejects calls to destroyed or unknown contracts, retrying won't help
+            Err(err) if !err.is_retryable() => {
+                tracing::warn!(contract_id = contract_id, error = %err, "Calls rejected, skipping");
+                Ok(vec![])
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    // List the transactions in the mempool, grouped by chain index.
+    // GET:/mempool/transactions
+    pub async fn get_mempool_transactions(&self) -> Result<Vec<MempoolTransactions>> {
+        self.get("mempool/transactions").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{healthy_node, serve};
+
+    #[test]
+    fn test_backoff_is_bounded_and_grows() {
+        let policy = RetryPolicy {
+            timeout: Duration::from_secs(1),
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+            max_retry_after: Duration::from_secs(60),
+        };
+
+        for attempt in 1..=10 {
+            let expected = (100u64 << (attempt - 1)).min(1000);
+            let delay = policy.backoff(attempt).as_millis() as u64;
+            assert!(delay >= expected / 2, "attempt {attempt}: {delay} < {}", expected / 2);
+            assert!(delay <= expected, "attempt {attempt}: {delay} > {expected}");
+        }
+    }
+
+    #[test]
+    fn test_delay_honours_retry_after() {
+        let policy = RetryPolicy::default();
+        let err = error_from_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            Some(Duration::from_secs(3)),
+            "".to_string(),
+        );
+        assert_eq!(policy.delay_for(1, &err), Duration::from_secs(3));
+
+        // Retry-After is honoured beyond the maximum backoff
+        let err = error_from_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some(Duration::from_secs(60)),
+            "".to_string(),
+        );
+        assert_eq!(policy.delay_for(1, &err), Duration::from_secs(60));
+
+        // And capped by its own ceiling
+        let err = error_from_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some(Duration::from_secs(3600)),
+            "".to_string(),
+        );
+        assert_eq!(policy.delay_for(1, &err), policy.max_retry_after);
+    }
+
+    #[test]
+    fn test_error_from_response() {
+        let err = error_from_response(
+            StatusCode::NOT_FOUND,
+            None,
+            r#"{"detail": "Block not found"}"#.to_string(),
+        );
+        match &err {
+            ClientError::Api { status, detail, .. } => {
+                assert_eq!(*status, StatusCode::NOT_FOUND);
+                assert_eq!(detail, "Block not found");
+            }
+            _ => panic!("Expected an API error, got {:?}", err),
+        }
+        assert!(!err.is_retryable());
+
+        let err = error_from_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            None,
+            r#"{"detail": "Self clique unsynced"}"#.to_string(),
+        );
+        assert!(err.is_retryable());
+
+        let err =
+            error_from_response(StatusCode::BAD_GATEWAY, None, "<html>Bad Gateway</html>".into());
+        assert!(matches!(err, ClientError::Status { .. }));
+        assert_eq!(err.status(), Some(StatusCode::BAD_GATEWAY));
+        assert!(err.is_retryable());
+    }
+
+    fn status(height: Option<i64>, healthy: bool, consecutive_failures: u32) -> EndpointStatus {
+        EndpointStatus { base_url: String::new(), healthy, height, consecutive_failures }
+    }
+
+    #[test]
+    fn test_select_endpoint() {
+        // Prefer the most up-to-date healthy node
+        let statuses =
+            vec![status(Some(10), true, 0), status(Some(12), true, 0), status(Some(15), false, 1)];
+        assert_eq!(select_endpoint(&statuses), 1);
+
+        // Ties are broken by configuration order
+        let statuses = vec![status(None, true, 0), status(None, true, 0)];
+        assert_eq!(select_endpoint(&statuses), 0);
+
+        // When every node is down, probe the one that failed the least
+        let statuses = vec![status(None, false, 3), status(None, false, 1)];
+        assert_eq!(select_endpoint(&statuses), 1);
+    }
+
+    #[test]
+    fn test_health_check_marks_lagging_nodes() {
+        let endpoints = Endpoints::new(vec!["a".into(), "b".into(), "c".into()]);
+        endpoints.apply_health_check(vec![Some(100), Some(90), None], 3);
+
+        let statuses = endpoints.snapshot();
+        assert!(statuses[0].healthy);
+        assert!(!statuses[1].healthy);
+        assert!(!statuses[2].healthy);
+        assert_eq!(endpoints.select(), 0);
+
+        endpoints.record_failure(0);
+        assert_eq!(endpoints.snapshot()[0].consecutive_failures, 1);
+        endpoints.record_success(0);
+        assert!(endpoints.snapshot()[0].healthy);
+    }
+
+    #[test]
+    fn test_network_base_urls() {
+        let network = Network::Custom("http://node1:12973/, http://node2:12973".into());
+        assert_eq!(network.base_urls(), vec!["http://node1:12973", "http://node2:12973"]);
+        assert_eq!(network.base_url(), "http://node1:12973");
+    }
+
+    #[test]
+    fn test_client_requires_a_node_url() {
+        let client = |network| Client::new_with_retry_policy(network, RetryPolicy::no_retry());
+        assert!(matches!(client(Network::Custom(",".into())), Err(ClientError::NoNodeUrl)));
+        assert!(matches!(
+            client(Network::Custom("node.mainnet.alephium.org".into())),
+            Err(ClientError::Url(_))
+        ));
+        assert!(client(Network::Custom("http://node:12973".into())).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failover_without_retries() {
+        let failing = serve(|path| healthy_node(path).unwrap_or((503, "{}"))).await;
+        let working = serve(|path| healthy_node(path).unwrap_or((200, "true"))).await;
+        let client = Client::new_with_endpoints(
+            vec![failing, working],
+            RetryPolicy::no_retry(),
+            FailoverPolicy::default(),
+        )
+        .unwrap();
+
+        // The first node is preferred, and the second one is tried although retries are off
+        assert!(client.is_block_in_main_chain("block").await.unwrap());
+        let endpoints = client.endpoints();
+        assert!(!endpoints[0].healthy);
+        assert!(endpoints[1].healthy);
+    }
+
+    #[tokio::test]
+    async fn test_call_contract_methods_errors() {
+        const CONTRACT_ID: &str =
+            "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800";
+        let client = |base_url| {
+            Client::new_with_endpoints(
+                vec![base_url],
+                RetryPolicy::no_retry(),
+                FailoverPolicy::default(),
+            )
+            .unwrap()
+        };
+
+        // A contract the node refuses to call has no results
+        let rejecting = serve(|path| {
+            healthy_node(path).unwrap_or((400, r#"{"detail": "Contract not found"}"#))
+        })
+        .await;
+        let results = client(rejecting).call_contract_methods(CONTRACT_ID, &[0, 1], 4).await;
+        assert!(results.unwrap().is_empty());
+
+        // An unavailable node is an error, to be retried
+        let unavailable = serve(|path| healthy_node(path).unwrap_or((503, "{}"))).await;
+        let results = client(unavailable).call_contract_methods(CONTRACT_ID, &[0, 1], 4).await;
+        assert!(results.is_err());
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 5 "), Some(Duration::from_secs(5)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
     }
 }
@@ -3,6 +3,18 @@ pub enum ProcessorConfig {
     BlockProcessor,
     EventProcessor,
     LendingContractProcessor(String),
+    TransactionProcessor,
+    UtxoProcessor,
+    TokenTransferProcessor,
+    /// Tracks contract creations and destructions, and the contract states when
+    /// `snapshot_states` is set.
+    ContractProcessor {
+        snapshot_states: bool,
+    },
+    TokenRegistryProcessor,
+    NftProcessor,
+    /// Indexes the pairs created by the DEX factory at the given contract address.
+    DexProcessor(String),
 }
 
 impl ProcessorConfig {
@@ -12,6 +24,13 @@ impl ProcessorConfig {
             ProcessorConfig::BlockProcessor => "block_processor",
             ProcessorConfig::EventProcessor => "event_processor",
             ProcessorConfig::LendingContractProcessor(_) => "lending_contract_processor",
+            ProcessorConfig::TransactionProcessor => "transaction_processor",
+            ProcessorConfig::UtxoProcessor => "utxo_processor",
+            ProcessorConfig::TokenTransferProcessor => "token_transfer_processor",
+            ProcessorConfig::ContractProcessor { .. } => "contract_processor",
+            ProcessorConfig::TokenRegistryProcessor => "token_registry_processor",
+            ProcessorConfig::NftProcessor => "nft_processor",
+            ProcessorConfig::DexProcessor(_) => "dex_processor",
         }
     }
 }
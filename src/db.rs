@@ -20,7 +20,7 @@ pub type DbPool = Pool<AsyncPgConnection>;
 pub type DbPoolConnection<'a> = PooledConnection<'a, AsyncPgConnection>;
 
 // Establish a connection to the database
-fn establish_connection(database_url: &str) -> BoxFuture<ConnectionResult<AsyncPgConnection>> {
+fn establish_connection(database_url: &str) -> BoxFuture<'_, ConnectionResult<AsyncPgConnection>> {
     use native_tls::{Certificate, TlsConnector};
     use postgres_native_tls::MakeTlsConnector;
 
@@ -88,6 +88,9 @@ pub async fn new_db_pool(
 }
 
 // Run pending migrations
-pub fn run_pending_migrations<DB: diesel::backend::Backend>(conn: &mut impl MigrationHarness<DB>) {
-    conn.run_pending_migrations(MIGRATIONS).expect("[Parser] Migrations failed!");
+pub fn run_pending_migrations<DB: diesel::backend::Backend>(
+    conn: &mut impl MigrationHarness<DB>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    conn.run_pending_migrations(MIGRATIONS)?;
+    Ok(())
 }
@@ -1,3 +1,4 @@
+pub mod abi;
 pub mod client;
 pub mod config;
 pub mod db;
@@ -5,6 +6,8 @@ pub mod models;
 pub mod processors;
 pub mod repository;
 pub mod schema;
+#[cfg(test)]
+pub(crate) mod test_utils;
 pub mod types;
 pub mod utils;
 pub mod worker;
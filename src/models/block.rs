@@ -25,16 +25,143 @@ pub struct BlockModel {
 }
 
 impl BlockModel {
-    // fix the group number optionality
+    /// Returns the hash of the parent block, in the same chain, or `None` for a genesis block
+    /// or when the deps don't match the group count.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_num` - The number of groups of the network, `DEFAULT_GROUP_NUM` if `None`.
     pub fn parent(&self, group_num: Option<i64>) -> Option<BlockHash> {
         if self.height == 0 {
-            None
-        } else {
-            Some(self.get_deps()[group_num.unwrap_or(DEFAULT_GROUP_NUM) as usize].clone())
+            return None;
         }
+        let group_num = group_num.unwrap_or(DEFAULT_GROUP_NUM);
+        if self.deps.len() as i64 != 2 * group_num - 1 {
+            return None;
+        }
+        let index = parent_dep_index(self.chain_from, self.chain_to, group_num)?;
+        self.deps[index].clone()
     }
 
     pub fn get_deps(&self) -> Vec<BlockHash> {
         self.deps.iter().map(|x| x.clone().unwrap()).collect()
     }
 }
+
+/// Returns the index of the parent hash in the deps of a block of chain `chain_from -> chain_to`.
+///
+/// The deps of a block hold the latest known block of each other group (`group_num - 1` deps,
+/// `chain_from` excluded), followed by the latest block of each chain `chain_from -> i` for `i`
+/// in `0..group_num`. The parent is the dep of the block's own chain.
+///
+/// # Returns
+///
+/// The index, or `None` if the chain index is out of range for `group_num`.
+pub fn parent_dep_index(chain_from: i64, chain_to: i64, group_num: i64) -> Option<usize> {
+    let groups = 0..group_num;
+    if !groups.contains(&chain_from) || !groups.contains(&chain_to) {
+        return None;
+    }
+    Some((group_num - 1 + chain_to) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Client, Network};
+    use crate::types::BlockHeaderEntry;
+    use crate::utils::timestamp_millis_to_naive_datetime;
+
+    /// Returns a block of chain `chain_from -> chain_to` of a 4 groups network, whose deps are
+    /// labelled after what they point to: `group-<g>` for the other groups, then
+    /// `chain-<from>-<to>` for the chains of the block's group.
+    fn block_fixture(chain_from: i64, chain_to: i64, height: i64) -> BlockModel {
+        let other_groups =
+            (0..4).filter(|group| *group != chain_from).map(|g| format!("group-{g}"));
+        let own_chains = (0..4).map(|to| format!("chain-{chain_from}-{to}"));
+        BlockModel {
+            hash: format!("block-{chain_from}-{chain_to}-{height}"),
+            timestamp: NaiveDateTime::default(),
+            chain_from,
+            chain_to,
+            height,
+            deps: other_groups.chain(own_chains).map(Some).collect(),
+            nonce: String::new(),
+            version: "0".to_string(),
+            dep_state_hash: String::new(),
+            txs_hash: String::new(),
+            tx_number: 0,
+            target: String::new(),
+            main_chain: true,
+            ghost_uncles: serde_json::Value::Array(vec![]),
+        }
+    }
+
+    /// Returns the model of a block given its header, with the fields read by `parent`.
+    fn block_from_header(header: BlockHeaderEntry) -> BlockModel {
+        BlockModel {
+            hash: header.hash,
+            timestamp: timestamp_millis_to_naive_datetime(header.timestamp),
+            chain_from: header.chain_from,
+            chain_to: header.chain_to,
+            height: header.height,
+            deps: header.deps.into_iter().map(Some).collect(),
+            ..block_fixture(header.chain_from, header.chain_to, header.height)
+        }
+    }
+
+    #[test]
+    fn test_parent_dep_index() {
+        // One dep per other group, then one per chain of the group
+        assert_eq!(parent_dep_index(0, 0, 4), Some(3));
+        assert_eq!(parent_dep_index(2, 3, 4), Some(6));
+        assert_eq!(parent_dep_index(3, 1, 4), Some(4));
+        assert_eq!(parent_dep_index(1, 1, 2), Some(2));
+        assert_eq!(parent_dep_index(0, 4, 4), None);
+        assert_eq!(parent_dep_index(-1, 0, 4), None);
+    }
+
+    #[test]
+    fn test_block_model_parent() {
+        let block = block_fixture(0, 0, 3402117);
+        assert_eq!(block.parent(None).as_deref(), Some("chain-0-0"));
+
+        let block = block_fixture(2, 3, 3398540);
+        assert_eq!(block.parent(Some(4)).as_deref(), Some("chain-2-3"));
+
+        let block = block_fixture(3, 1, 3401966);
+        assert_eq!(block.parent(None).as_deref(), Some("chain-3-1"));
+
+        // The deps don't match the group count
+        assert_eq!(block.parent(Some(2)), None);
+
+        // Genesis block
+        let mut genesis = block;
+        genesis.height = 0;
+        assert_eq!(genesis.parent(None), None);
+    }
+
+    /// Checks `parent` against mainnet blocks of every chain, the chains across groups
+    /// included: the parent must be the main chain block of the same chain one height below.
+    /// Needs a mainnet node, `MAINNET_NODE_URL` selects another one than the public node.
+    #[tokio::test]
+    #[ignore = "needs a mainnet node"]
+    async fn test_block_model_parent_mainnet() {
+        let client = Client::new(Network::Mainnet).unwrap();
+        for (chain_from, chain_to) in (0..4).flat_map(|from| (0..4).map(move |to| (from, to))) {
+            // Below the tip, so that the main chain is settled
+            let tip = client.get_chain_info(chain_from, chain_to).await.unwrap().current_height;
+            let height = tip - 10;
+            let hashes = client.get_hashes_at_height(chain_from, chain_to, height).await.unwrap();
+            let header = client.get_block_header(&hashes.headers[0]).await.unwrap();
+
+            let parent = block_from_header(header).parent(None).unwrap();
+            let parent_header = client.get_block_header(&parent).await.unwrap();
+            assert_eq!((parent_header.chain_from, parent_header.chain_to), (chain_from, chain_to));
+            assert_eq!(parent_header.height, height - 1);
+            let parents =
+                client.get_hashes_at_height(chain_from, chain_to, height - 1).await.unwrap();
+            assert_eq!(parents.headers[0], parent, "chain {chain_from}->{chain_to} at {height}");
+        }
+    }
+}
@@ -0,0 +1,13 @@
+/// Error of the conversions of the `DbEnum` columns, raised for values with no variant.
+#[derive(Debug, thiserror::Error)]
+#[error("CustomError: {msg}, {status}")]
+pub struct CustomError {
+    msg: String,
+    status: u16,
+}
+
+impl CustomError {
+    pub fn not_found(msg: String) -> Self {
+        Self { msg, status: 404 }
+    }
+}
@@ -8,4 +8,8 @@ pub struct EventModel {
     pub contract_address: String,
     pub event_index: i32,
     pub fields: serde_json::Value,
+    pub block_hash: String,
+    pub main_chain: bool,
+    /// Position of the event among the events of its block.
+    pub event_order: i32,
 }
@@ -1,52 +1,176 @@
-use crate::types::BlockAndEvents;
+use std::str::FromStr;
+
+use crate::types::{BlockAndEvents, BlockEntry};
+use anyhow::{Context, Result};
+use bigdecimal::BigDecimal;
 
 pub mod block;
+pub mod error;
 pub mod event;
 pub mod processor_status;
+pub mod token;
 pub mod transaction;
 
 use block::BlockModel;
 use event::EventModel;
+use transaction::TransactionModel;
 
 pub fn convert_bwe_to_block_models(blocks: Vec<Vec<BlockAndEvents>>) -> Vec<BlockModel> {
     let mut models = Vec::new();
     for bes in blocks {
         for be in bes {
-            let b = be.block;
-            models.push(BlockModel {
-                hash: b.hash,
-                timestamp: crate::utils::timestamp_millis_to_naive_datetime(b.timestamp),
-                chain_from: b.chain_from,
-                chain_to: b.chain_to,
-                height: b.height,
-                deps: b.deps.iter().map(|x| Some(x.clone())).collect(),
-                nonce: b.nonce,
-                version: b.version.to_string(),
-                dep_state_hash: b.dep_state_hash,
-                txs_hash: b.txs_hash.to_string(),
-                tx_number: b.transactions.len() as i64,
-                target: b.target,
-                main_chain: b.main_chain,
-                ghost_uncles: serde_json::to_value(b.ghost_uncles).unwrap_or_default(),
-            });
+            models.push(convert_block_entry_to_block_model(be.block));
         }
     }
     models
 }
 
+pub fn convert_block_entry_to_block_model(b: BlockEntry) -> BlockModel {
+    BlockModel {
+        hash: b.hash,
+        timestamp: crate::utils::timestamp_millis_to_naive_datetime(b.timestamp),
+        chain_from: b.chain_from,
+        chain_to: b.chain_to,
+        height: b.height,
+        deps: b.deps.iter().map(|x| Some(x.clone())).collect(),
+        nonce: b.nonce,
+        version: b.version.to_string(),
+        dep_state_hash: b.dep_state_hash,
+        txs_hash: b.txs_hash.to_string(),
+        tx_number: b.transactions.len() as i64,
+        target: b.target,
+        main_chain: b.main_chain,
+        ghost_uncles: serde_json::to_value(b.ghost_uncles).unwrap_or_default(),
+    }
+}
+
+/// Converts the transactions of a block into models, carrying the block hash, main chain status
+/// and timestamp of the block.
+pub fn convert_block_entry_to_tx_models(b: &BlockEntry) -> Result<Vec<TransactionModel>> {
+    let block_timestamp = crate::utils::timestamp_millis_to_naive_datetime(b.timestamp);
+    b.transactions
+        .iter()
+        .map(|tx| {
+            Ok(TransactionModel {
+                tx_hash: tx.unsigned.tx_id.clone(),
+                unsigned: serde_json::to_value(&tx.unsigned)?,
+                script_execution_ok: tx.script_execution_ok,
+                contract_inputs: serde_json::to_value(&tx.contract_inputs)?,
+                generated_outputs: serde_json::to_value(&tx.generated_outputs)?,
+                input_signatures: tx.input_signatures.iter().cloned().map(Some).collect(),
+                script_signatures: tx.script_signatures.iter().cloned().map(Some).collect(),
+                created_at: None,
+                updated_at: None,
+                main_chain: b.main_chain,
+                block_hash: b.hash.clone(),
+                block_timestamp,
+                gas_amount: tx.unsigned.gas_amount,
+                gas_price: BigDecimal::from_str(&tx.unsigned.gas_price).with_context(|| {
+                    format!(
+                        "Invalid gas price {} in tx {}",
+                        tx.unsigned.gas_price, tx.unsigned.tx_id
+                    )
+                })?,
+            })
+        })
+        .collect()
+}
+
 pub fn convert_bwe_to_event_models(blocks: Vec<Vec<BlockAndEvents>>) -> Vec<EventModel> {
     let mut models = Vec::new();
     for bes in blocks {
         for be in bes {
-            for e in be.events {
+            for (order, e) in be.events.into_iter().enumerate() {
                 models.push(EventModel {
                     tx_id: e.tx_id,
                     contract_address: e.contract_address,
                     event_index: e.event_index,
                     fields: serde_json::to_value(e.fields).unwrap_or_default(), // TODO: need error handling here for retry?
+                    block_hash: be.block.hash.clone(),
+                    main_chain: be.block.main_chain,
+                    event_order: order as i32,
                 });
             }
         }
     }
     models
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::block_and_events;
+    use serde_json::json;
+
+    #[test]
+    fn test_convert_block_entry_to_tx_models() {
+        let block: BlockEntry = serde_json::from_value(json!({
+            "hash": "00000000000006f8c2bcaac93c5a23df8fba7119ba139d80a49d0303bbf84850",
+            "parent": "0000000000015b5e1a1f1c8b3a4f5d1e2c3b4a5f6e7d8c9b0a1f2e3d4c5b6a79",
+            "mainChain": false,
+            "timestamp": 1735689600123i64,
+            "chainFrom": 0,
+            "chainTo": 0,
+            "height": 1000,
+            "deps": [],
+            "transactions": [{
+                "unsigned": {
+                    "txId": "tx123",
+                    "version": 0,
+                    "networkId": 0,
+                    "gasAmount": 20000,
+                    "gasPrice": "100000000000",
+                    "inputs": [],
+                    "fixedOutputs": []
+                },
+                "scriptExecutionOk": false,
+                "contractInputs": [],
+                "generatedOutputs": [],
+                "inputSignatures": ["sig"],
+                "scriptSignatures": []
+            }],
+            "nonce": "nonce_value",
+            "version": 0,
+            "depStateHash": "dep_hash",
+            "txsHash": "txs_hash",
+            "target": "target_value",
+            "ghostUncles": []
+        }))
+        .unwrap();
+
+        let txs = convert_block_entry_to_tx_models(&block).unwrap();
+        assert_eq!(txs.len(), 1);
+        let tx = &txs[0];
+        assert_eq!(tx.tx_hash, "tx123");
+        assert_eq!(tx.block_hash, block.hash);
+        assert!(!tx.main_chain);
+        assert!(!tx.script_execution_ok);
+        assert_eq!(tx.block_timestamp.and_utc().timestamp_millis(), 1735689600123);
+        assert_eq!(tx.gas_amount, 20000);
+        assert_eq!(tx.gas_price, BigDecimal::from(100000000000u64));
+        assert_eq!(tx.input_signatures, vec![Some("sig".to_string())]);
+        assert_eq!(tx.unsigned["txId"], "tx123");
+
+        let mut block = block;
+        block.transactions[0].unsigned.gas_price = "not a number".into();
+        assert!(convert_block_entry_to_tx_models(&block).is_err());
+    }
+
+    #[test]
+    fn test_convert_bwe_to_event_models() {
+        // Two contracts called by the same transaction emit an event with the same index
+        let events = json!([
+            { "txId": "tx123", "contractAddress": "contract_a", "eventIndex": 0, "fields": [] },
+            { "txId": "tx123", "contractAddress": "contract_b", "eventIndex": 0, "fields": [] }
+        ]);
+        let be = block_and_events("block_hash", true, 1735689600000, json!([]), events);
+
+        let events = convert_bwe_to_event_models(vec![vec![be]]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].contract_address, "contract_a");
+        assert_eq!(events[0].event_order, 0);
+        assert_eq!(events[1].contract_address, "contract_b");
+        assert_eq!(events[1].event_order, 1);
+        assert!(events.iter().all(|event| event.block_hash == "block_hash" && event.main_chain));
+    }
+}
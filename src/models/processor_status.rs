@@ -8,3 +8,18 @@ pub struct ProcessorStatusModel {
     pub processor: String,
     pub last_timestamp: i64,
 }
+
+/// Last block processed by a processor on a chain.
+#[derive(
+    Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Eq, Serialize, AsChangeset,
+)]
+#[diesel(table_name = crate::schema::processor_chain_status)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ProcessorChainStatusModel {
+    pub processor: String,
+    pub chain_from: i64,
+    pub chain_to: i64,
+    pub block_hash: String,
+    pub height: i64,
+    pub timestamp: i64,
+}
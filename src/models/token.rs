@@ -0,0 +1,82 @@
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::Serialize;
+
+/// A token and its metadata, empty for tokens not implementing the fungible token standard.
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::tokens)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TokenModel {
+    pub token_id: String,
+    pub contract_address: Option<String>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    pub total_supply: Option<BigDecimal>,
+    /// `None` while the node rejects the calls reading the metadata.
+    pub is_fungible: Option<bool>,
+    pub first_seen_block_hash: Option<String>,
+    pub first_seen_timestamp: Option<NaiveDateTime>,
+}
+
+/// A token found in the outputs of a block, before its metadata is fetched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenSighting {
+    pub token_id: String,
+    pub block_hash: String,
+    pub timestamp: NaiveDateTime,
+}
+
+impl TokenModel {
+    /// Converts an amount in the smallest unit of the token to whole tokens, if the decimals
+    /// of the token are known.
+    pub fn to_whole_units(&self, amount: &BigDecimal) -> Option<BigDecimal> {
+        let decimals = self.decimals?;
+        Some(amount / BigDecimal::new(1.into(), -i64::from(decimals)))
+    }
+
+    /// Renders an amount in the smallest unit of the token as whole tokens followed by the
+    /// symbol, falling back to the raw amount and token id when the metadata is unknown.
+    pub fn format_amount(&self, amount: &BigDecimal) -> String {
+        match (self.to_whole_units(amount), self.symbol.as_ref()) {
+            (Some(units), Some(symbol)) => format!("{} {}", units.normalized(), symbol),
+            _ => format!("{} {}", amount, self.token_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn token(decimals: Option<i32>, symbol: Option<&str>) -> TokenModel {
+        TokenModel {
+            token_id: "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800".into(),
+            contract_address: None,
+            name: None,
+            symbol: symbol.map(String::from),
+            decimals,
+            total_supply: None,
+            is_fungible: Some(decimals.is_some()),
+            first_seen_block_hash: None,
+            first_seen_timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_format_amount() {
+        let amount = BigDecimal::from_str("1234500000000000000").unwrap();
+        assert_eq!(token(Some(18), Some("ALPH")).format_amount(&amount), "1.2345 ALPH");
+        assert_eq!(token(Some(0), Some("NFT")).format_amount(&BigDecimal::from(3)), "3 NFT");
+        assert_eq!(
+            token(None, None).format_amount(&amount),
+            "1234500000000000000 1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800"
+        );
+        assert_eq!(
+            token(Some(6), None).to_whole_units(&BigDecimal::from(2_500_000)),
+            Some(BigDecimal::from_str("2.5").unwrap())
+        );
+    }
+}
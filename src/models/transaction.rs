@@ -1,3 +1,4 @@
+use bigdecimal::BigDecimal;
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -15,4 +16,9 @@ pub struct TransactionModel {
     pub script_signatures: Vec<Option<String>>,
     pub created_at: Option<NaiveDateTime>,
     pub updated_at: Option<NaiveDateTime>,
+    pub main_chain: bool,
+    pub block_hash: String,
+    pub block_timestamp: NaiveDateTime,
+    pub gas_amount: i32,
+    pub gas_price: BigDecimal,
 }
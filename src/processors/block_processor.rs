@@ -61,10 +61,14 @@ impl ProcessorTrait for BlockProcessor {
     }
 }
 
-/// Insert blocks into the database.
+/// Insert blocks into the database, skipping blocks that are already stored.
 pub async fn insert_to_db(db: Arc<DbPool>, blocks: Vec<BlockModel>) -> Result<()> {
     let mut conn = db.get().await?;
-    insert_into(crate::schema::blocks::table).values(&blocks).execute(&mut conn).await?;
+    insert_into(crate::schema::blocks::table)
+        .values(&blocks)
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await?;
     Ok(())
 }
 
@@ -0,0 +1,524 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use futures::{StreamExt, TryStreamExt};
+use reqwest::StatusCode;
+use serde::Serialize;
+
+use crate::{
+    client::{Client, ClientError},
+    config::ProcessorConfig,
+    db::DbPool,
+    processors::ProcessorTrait,
+    types::{BlockAndEvents, BlockHash, ContractState, EventFieldType, Output},
+    utils::{
+        address_from_unlock_script, contract_id_from_address, timestamp_millis_to_naive_datetime,
+    },
+};
+
+/// Index of the system event emitted by the node when a contract is created.
+pub const CREATE_CONTRACT_EVENT_INDEX: i32 = -1;
+/// Index of the system event emitted by the node when a contract is destroyed.
+pub const DESTROY_CONTRACT_EVENT_INDEX: i32 = -2;
+
+/// Maximum number of contract states requested from the node at once.
+const STATE_FETCH_CONCURRENCY: usize = 8;
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::contracts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ContractModel {
+    pub address: String,
+    pub contract_id: String,
+    pub parent_address: Option<String>,
+    pub creator_address: Option<String>,
+    pub code_hash: Option<String>,
+    pub std_interface_id: Option<String>,
+    pub creation_block_hash: String,
+    pub creation_tx_id: String,
+    pub creation_timestamp: NaiveDateTime,
+    pub destruction_block_hash: Option<String>,
+    pub destruction_tx_id: Option<String>,
+    pub destruction_timestamp: Option<NaiveDateTime>,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::contract_states)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ContractStateModel {
+    pub address: String,
+    pub block_hash: String,
+    pub tx_id: String,
+    pub timestamp: NaiveDateTime,
+    pub code_hash: String,
+    pub imm_fields: serde_json::Value,
+    pub mut_fields: serde_json::Value,
+    pub asset: serde_json::Value,
+}
+
+/// A contract touched by a transaction: created, destroyed, or whose output was regenerated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractActivity {
+    pub address: String,
+    pub block_hash: String,
+    pub tx_id: String,
+    pub timestamp: NaiveDateTime,
+}
+
+/// Contract creations, destructions and updates found in a batch of blocks.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ContractChanges {
+    pub created: Vec<ContractModel>,
+    pub destroyed: Vec<ContractActivity>,
+    pub updated: Vec<ContractActivity>,
+}
+
+/// Tracks the contracts created and destroyed in main chain transactions, as reported by the
+/// node's contract creation and destruction system events.
+///
+/// The code hash of new contracts is read from `/contracts/{address}/state`. With
+/// `snapshot_states`, the state of every contract touched by a transaction is also stored
+/// after each window. The node only serves the latest state, so snapshots describe the contract
+/// when the window was processed rather than right after the transaction.
+pub struct ContractProcessor {
+    connection_pool: Arc<DbPool>,
+    client: Arc<Client>,
+    snapshot_states: bool,
+}
+
+impl ContractProcessor {
+    pub fn new(connection_pool: Arc<DbPool>, client: Arc<Client>, snapshot_states: bool) -> Self {
+        Self { connection_pool, client, snapshot_states }
+    }
+
+    async fn insert(&self, blocks: Vec<BlockAndEvents>) -> Result<()> {
+        let mut changes = convert_to_contract_changes(blocks);
+        if changes.created.is_empty() && changes.destroyed.is_empty() && changes.updated.is_empty()
+        {
+            return Ok(());
+        }
+
+        let created: Vec<String> =
+            changes.created.iter().map(|contract| contract.address.clone()).collect();
+        let states = self.fetch_states(&created).await?;
+        for contract in changes.created.iter_mut() {
+            contract.code_hash = states.get(&contract.address).map(|state| state.code_hash.clone());
+        }
+
+        let mut snapshots = Vec::new();
+        if self.snapshot_states {
+            // Keep the last activity of each contract, the state being read once
+            let mut last_activity: HashMap<String, ContractActivity> = HashMap::new();
+            for activity in changes.updated.iter() {
+                last_activity.insert(activity.address.clone(), activity.clone());
+            }
+            let addresses: Vec<String> = last_activity.keys().cloned().collect();
+            for (address, state) in self.fetch_states(&addresses).await? {
+                snapshots.push(convert_to_contract_state_model(&last_activity[&address], state)?);
+            }
+        }
+
+        tracing::info!(
+            processor_name = ?self.name(),
+            created = ?changes.created.len(),
+            destroyed = ?changes.destroyed.len(),
+            snapshots = ?snapshots.len(),
+            "Found contract changes"
+        );
+        insert_contract_changes(
+            self.connection_pool.clone(),
+            changes.created,
+            changes.destroyed,
+            snapshots,
+        )
+        .await
+    }
+
+    /// Fetches the states of a list of contracts, skipping the ones that no longer exist.
+    async fn fetch_states(&self, addresses: &[String]) -> Result<HashMap<String, ContractState>> {
+        let states: Vec<(String, Option<ContractState>)> =
+            futures::stream::iter(addresses.to_vec())
+                .map(|address| {
+                    let client = self.client.clone();
+                    async move {
+                        match client.get_contract_state(&address).await {
+                            Ok(state) => Ok((address, Some(state))),
+                            Err(err) if is_not_found(&err) => Ok((address, None)),
+                            Err(err) => Err(err),
+                        }
+                    }
+                })
+                .buffered(STATE_FETCH_CONCURRENCY)
+                .try_collect()
+                .await?;
+        Ok(states.into_iter().filter_map(|(address, state)| Some((address, state?))).collect())
+    }
+}
+
+impl Debug for ContractProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "ContractProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+#[async_trait]
+impl ProcessorTrait for ContractProcessor {
+    fn name(&self) -> &'static str {
+        ProcessorConfig::ContractProcessor { snapshot_states: false }.name()
+    }
+
+    fn connection_pool(&self) -> &Arc<DbPool> {
+        &self.connection_pool
+    }
+
+    async fn process_blocks(
+        &self,
+        _from: i64,
+        _to: i64,
+        blocks: Vec<Vec<BlockAndEvents>>,
+    ) -> Result<()> {
+        self.insert(blocks.into_iter().flatten().collect()).await
+    }
+
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        revert_contract_changes(self.connection_pool.clone(), orphaned).await?;
+        self.insert(replacements).await
+    }
+}
+
+fn is_not_found(err: &ClientError) -> bool {
+    err.status() == Some(StatusCode::NOT_FOUND)
+}
+
+/// Returns the contracts created, destroyed and updated by the transactions of the main chain
+/// blocks. The code hash of the created contracts is left empty.
+pub fn convert_to_contract_changes(
+    blocks: impl IntoIterator<Item = BlockAndEvents>,
+) -> ContractChanges {
+    let mut changes = ContractChanges::default();
+    for be in blocks.into_iter().filter(|be| be.block.main_chain) {
+        let timestamp = timestamp_millis_to_naive_datetime(be.block.timestamp);
+        let activity = |address: &str, tx_id: &str| ContractActivity {
+            address: address.to_string(),
+            block_hash: be.block.hash.clone(),
+            tx_id: tx_id.to_string(),
+            timestamp,
+        };
+
+        for event in be.events.iter() {
+            let address = match event.fields.as_slice() {
+                [field, ..] if field.field_type == EventFieldType::Address => {
+                    match field.value.as_str() {
+                        Some(address) => address,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            match event.event_index {
+                CREATE_CONTRACT_EVENT_INDEX => {
+                    let tx =
+                        be.block.transactions.iter().find(|tx| tx.unsigned.tx_id == event.tx_id);
+                    let creator_address = tx.and_then(|tx| {
+                        tx.unsigned
+                            .inputs
+                            .iter()
+                            .find_map(|input| address_from_unlock_script(&input.unlock_script))
+                    });
+                    let parent_address = event
+                        .fields
+                        .get(1)
+                        .filter(|field| field.field_type == EventFieldType::Address)
+                        .and_then(|field| field.value.as_str())
+                        .map(String::from);
+                    let std_interface_id = event
+                        .fields
+                        .get(2)
+                        .filter(|field| field.field_type == EventFieldType::ByteVec)
+                        .and_then(|field| field.value.as_str())
+                        .filter(|id| !id.is_empty())
+                        .map(String::from);
+                    let Some(contract_id) = contract_id_from_address(address) else {
+                        tracing::warn!(address = address, "Invalid contract address, skipping");
+                        continue;
+                    };
+                    changes.created.push(ContractModel {
+                        address: address.to_string(),
+                        contract_id,
+                        parent_address,
+                        creator_address,
+                        code_hash: None,
+                        std_interface_id,
+                        creation_block_hash: be.block.hash.clone(),
+                        creation_tx_id: event.tx_id.clone(),
+                        creation_timestamp: timestamp,
+                        destruction_block_hash: None,
+                        destruction_tx_id: None,
+                        destruction_timestamp: None,
+                    });
+                    changes.updated.push(activity(address, &event.tx_id));
+                }
+                DESTROY_CONTRACT_EVENT_INDEX => {
+                    changes.destroyed.push(activity(address, &event.tx_id));
+                }
+                _ => {}
+            }
+        }
+
+        // Contracts whose state may have changed regenerate their output
+        for tx in be.block.transactions.iter() {
+            for output in tx.generated_outputs.iter() {
+                if let Output::ContractOutput(output) = output {
+                    changes.updated.push(activity(&output.address, &tx.unsigned.tx_id));
+                }
+            }
+        }
+    }
+    changes
+}
+
+fn convert_to_contract_state_model(
+    activity: &ContractActivity,
+    state: ContractState,
+) -> Result<ContractStateModel> {
+    Ok(ContractStateModel {
+        address: activity.address.clone(),
+        block_hash: activity.block_hash.clone(),
+        tx_id: activity.tx_id.clone(),
+        timestamp: activity.timestamp,
+        code_hash: state.code_hash,
+        imm_fields: serde_json::to_value(state.imm_fields)?,
+        mut_fields: serde_json::to_value(state.mut_fields)?,
+        asset: serde_json::to_value(state.asset)?,
+    })
+}
+
+/// Insert contract creations and state snapshots and record contract destructions, in a single
+/// DB transaction.
+pub async fn insert_contract_changes(
+    db: Arc<DbPool>,
+    created: Vec<ContractModel>,
+    destroyed: Vec<ContractActivity>,
+    snapshots: Vec<ContractStateModel>,
+) -> Result<()> {
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            use crate::schema::contracts;
+
+            insert_into(contracts::table)
+                .values(&created)
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await?;
+            for activity in destroyed.iter() {
+                diesel::update(
+                    contracts::table
+                        .filter(contracts::address.eq(&activity.address))
+                        .filter(contracts::destruction_block_hash.is_null()),
+                )
+                .set((
+                    contracts::destruction_block_hash.eq(&activity.block_hash),
+                    contracts::destruction_tx_id.eq(&activity.tx_id),
+                    contracts::destruction_timestamp.eq(activity.timestamp),
+                ))
+                .execute(conn)
+                .await?;
+            }
+            insert_into(crate::schema::contract_states::table)
+                .values(&snapshots)
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await?;
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Delete the contracts created and state snapshots taken in a list of blocks, and bring back
+/// the contracts destroyed in them.
+pub async fn revert_contract_changes(db: Arc<DbPool>, block_hashes: &[BlockHash]) -> Result<()> {
+    use crate::schema::{contract_states, contracts};
+
+    if block_hashes.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(
+                contracts::table.filter(contracts::creation_block_hash.eq_any(block_hashes)),
+            )
+            .execute(conn)
+            .await?;
+            diesel::update(
+                contracts::table.filter(contracts::destruction_block_hash.eq_any(block_hashes)),
+            )
+            .set((
+                contracts::destruction_block_hash.eq(None::<String>),
+                contracts::destruction_tx_id.eq(None::<String>),
+                contracts::destruction_timestamp.eq(None::<NaiveDateTime>),
+            ))
+            .execute(conn)
+            .await?;
+            diesel::delete(
+                contract_states::table.filter(contract_states::block_hash.eq_any(block_hashes)),
+            )
+            .execute(conn)
+            .await?;
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Returns the latest creation of the contract at an address.
+pub async fn get_contract(db: Arc<DbPool>, address: &str) -> Result<Option<ContractModel>> {
+    use crate::schema::contracts;
+
+    let mut conn = db.get().await?;
+    let contract = contracts::table
+        .filter(contracts::address.eq(address))
+        .order_by(contracts::creation_timestamp.desc())
+        .select(ContractModel::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?;
+    Ok(contract)
+}
+
+/// Returns the live subcontracts of a contract.
+pub async fn get_subcontracts(db: Arc<DbPool>, parent_address: &str) -> Result<Vec<ContractModel>> {
+    use crate::schema::contracts;
+
+    let mut conn = db.get().await?;
+    let contracts = contracts::table
+        .filter(contracts::parent_address.eq(parent_address))
+        .filter(contracts::destruction_block_hash.is_null())
+        .order_by(contracts::creation_timestamp)
+        .select(ContractModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(contracts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::block_and_events;
+    use serde_json::json;
+
+    const LENDING: &str = "yuF1Sum4ricLFBc86h3RdjFsebR7ZXKBHm2S5sZmVsiF";
+    const LOAN: &str = "wCTeteGBeSEC54GpkS8jWBzYiYNTBUuTW3WzxGd9yExT";
+    const PUBLIC_KEY: &str = "0381818e63bd9e35a5489b52a430accefc608fd60aa2c7c0d1b393b5239aedf6b2";
+
+    fn block(main_chain: bool, events: serde_json::Value) -> BlockAndEvents {
+        let transactions = json!([{
+            "unsigned": {
+                "txId": "create_loan",
+                "version": 0,
+                "networkId": 0,
+                "gasAmount": 20000,
+                "gasPrice": "100000000000",
+                "inputs": [
+                    { "outputRef": { "hint": 1, "key": "a" }, "unlockScript": format!("00{}", PUBLIC_KEY) },
+                    { "outputRef": { "hint": 1, "key": "b" }, "unlockScript": "03" }
+                ],
+                "fixedOutputs": []
+            },
+            "scriptExecutionOk": true,
+            "contractInputs": [{ "hint": 3, "key": "lending_before" }],
+            "generatedOutputs": [{
+                "type": "ContractOutput",
+                "hint": 3,
+                "key": "lending_after",
+                "attoAlphAmount": "100000000000000000",
+                "address": LENDING,
+                "tokens": []
+            }],
+            "inputSignatures": [],
+            "scriptSignatures": []
+        }]);
+        block_and_events("block", main_chain, 1735689600000, transactions, events)
+    }
+
+    #[test]
+    fn test_convert_to_contract_changes() {
+        let events = json!([
+            {
+                "txId": "create_loan",
+                "contractAddress": "system",
+                "eventIndex": CREATE_CONTRACT_EVENT_INDEX,
+                "fields": [
+                    { "type": "Address", "value": LOAN },
+                    { "type": "Address", "value": LENDING },
+                    { "type": "ByteVec", "value": "" }
+                ]
+            },
+            {
+                "txId": "create_loan",
+                "contractAddress": "system",
+                "eventIndex": DESTROY_CONTRACT_EVENT_INDEX,
+                "fields": [{ "type": "Address", "value": "destroyed" }]
+            },
+            {
+                "txId": "create_loan",
+                "contractAddress": LENDING,
+                "eventIndex": 0,
+                "fields": [{ "type": "Address", "value": LOAN }]
+            }
+        ]);
+
+        let changes = convert_to_contract_changes(vec![block(true, events.clone())]);
+        assert_eq!(changes.created.len(), 1);
+        let loan = &changes.created[0];
+        assert_eq!(loan.address, LOAN);
+        assert_eq!(
+            loan.contract_id,
+            "25469eb0d0d0a55deea832924547b7b166c70a3554fe321e81886d3c18f19d64"
+        );
+        assert_eq!(loan.parent_address.as_deref(), Some(LENDING));
+        assert_eq!(
+            loan.creator_address.as_deref(),
+            Some("1FQuaJLe6BAcHYoQdhW2TMENVpRgGS8rpMtLRsy9ZjbPq")
+        );
+        assert_eq!(loan.std_interface_id, None);
+        assert_eq!(loan.code_hash, None);
+        assert_eq!(
+            (loan.creation_block_hash.as_str(), loan.creation_tx_id.as_str()),
+            ("block", "create_loan")
+        );
+
+        let destroyed: Vec<_> = changes.destroyed.iter().map(|a| a.address.as_str()).collect();
+        assert_eq!(destroyed, vec!["destroyed"]);
+        let updated: Vec<_> = changes.updated.iter().map(|a| a.address.as_str()).collect();
+        assert_eq!(updated, vec![LOAN, LENDING]);
+
+        // Blocks out of the main chain are ignored
+        assert_eq!(
+            convert_to_contract_changes(vec![block(false, events)]),
+            ContractChanges::default()
+        );
+    }
+}
@@ -0,0 +1,684 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::sync::{Arc, LazyLock};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use chrono::NaiveDateTime;
+use diesel::expression::AsExpression;
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel::sql_types::SmallInt;
+use diesel::FromSqlRow;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use diesel_enum::DbEnum;
+use serde::Serialize;
+
+use crate::{
+    abi::{AbiError, AbiValue, ContractAbi, DecodedEvent},
+    config::ProcessorConfig,
+    db::DbPool,
+    models::error::CustomError,
+    processors::ProcessorTrait,
+    repository::insert_in_chunks,
+    types::{BlockAndEvents, BlockHash, ContractEventByBlockHash},
+    utils::{address_from_contract_id, timestamp_millis_to_naive_datetime},
+};
+
+/// Events of the `alephium-dex` factory. The artifacts hold the `eventsSig` of the contracts,
+/// written after their event declarations.
+static FACTORY_ABI: LazyLock<ContractAbi> = LazyLock::new(|| {
+    ContractAbi::from_json(include_str!("artifacts/dex/TokenPairFactory.ral.json"))
+        .expect("Invalid TokenPairFactory artifact")
+});
+/// Events of the `alephium-dex` pairs.
+static PAIR_ABI: LazyLock<ContractAbi> = LazyLock::new(|| {
+    ContractAbi::from_json(include_str!("artifacts/dex/TokenPair.ral.json"))
+        .expect("Invalid TokenPair artifact")
+});
+
+/// Significant digits kept in swap prices.
+const PRICE_PRECISION: u64 = 30;
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::dex_pairs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DexPairModel {
+    pub address: String,
+    pub factory_address: String,
+    pub token0_id: String,
+    pub token1_id: String,
+    pub block_hash: String,
+    pub tx_id: String,
+    pub timestamp: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::dex_swaps)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DexSwapModel {
+    pub block_hash: String,
+    pub event_order: i32,
+    pub pair_address: String,
+    pub tx_id: String,
+    pub trader: String,
+    pub to_address: String,
+    pub amount0_in: BigDecimal,
+    pub amount1_in: BigDecimal,
+    pub amount0_out: BigDecimal,
+    pub amount1_out: BigDecimal,
+    /// Amount of token1 exchanged per unit of token0, in the smallest units of both tokens.
+    pub price: Option<BigDecimal>,
+    pub timestamp: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::dex_liquidity_actions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DexLiquidityActionModel {
+    pub block_hash: String,
+    pub event_order: i32,
+    pub pair_address: String,
+    pub tx_id: String,
+    pub action_type: LiquidityActionType,
+    pub sender: String,
+    pub amount0: BigDecimal,
+    pub amount1: BigDecimal,
+    pub liquidity: BigDecimal,
+    pub timestamp: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::dex_reserves)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DexReserveModel {
+    pub block_hash: String,
+    pub event_order: i32,
+    pub pair_address: String,
+    pub tx_id: String,
+    pub reserve0: BigDecimal,
+    pub reserve1: BigDecimal,
+    pub timestamp: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromSqlRow, DbEnum, Serialize, AsExpression)]
+#[diesel(sql_type = SmallInt)]
+#[diesel_enum(error_fn = CustomError::not_found)]
+#[diesel_enum(error_type = CustomError)]
+pub enum LiquidityActionType {
+    Mint,
+    Burn,
+}
+
+/// Pairs, swaps, liquidity actions and reserves found in a batch of blocks.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DexChanges {
+    pub pairs: Vec<DexPairModel>,
+    pub swaps: Vec<DexSwapModel>,
+    pub liquidity_actions: Vec<DexLiquidityActionModel>,
+    pub reserves: Vec<DexReserveModel>,
+}
+
+impl DexChanges {
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+            && self.swaps.is_empty()
+            && self.liquidity_actions.is_empty()
+            && self.reserves.is_empty()
+    }
+}
+
+/// Indexes the pairs of a Uniswap-v2-style DEX, as the `alephium-dex` contracts: the pairs
+/// created by the factory, and the swaps, liquidity actions and reserve syncs of every pair.
+///
+/// Pairs are discovered from the `PairCreated` events of the factory, so the processor must
+/// start before the first pair is created to index every pair. Events are decoded with the
+/// artifacts of the contracts shipped under `artifacts/dex`.
+pub struct DexProcessor {
+    connection_pool: Arc<DbPool>,
+    factory_address: String,
+}
+
+impl DexProcessor {
+    pub fn new(connection_pool: Arc<DbPool>, factory_address: String) -> Self {
+        Self { connection_pool, factory_address }
+    }
+
+    async fn insert(&self, blocks: Vec<BlockAndEvents>) -> Result<()> {
+        let blocks: Vec<BlockAndEvents> =
+            blocks.into_iter().filter(|be| be.block.main_chain).collect();
+        let emitters: Vec<String> = blocks
+            .iter()
+            .flat_map(|be| be.events.iter().map(|event| event.contract_address.clone()))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let known_pairs =
+            get_known_pairs(self.connection_pool.clone(), &self.factory_address, &emitters).await?;
+
+        let changes = convert_to_dex_changes(&blocks, &self.factory_address, known_pairs);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        tracing::info!(
+            processor_name = ?self.name(),
+            pairs = ?changes.pairs.len(),
+            swaps = ?changes.swaps.len(),
+            liquidity_actions = ?changes.liquidity_actions.len(),
+            reserves = ?changes.reserves.len(),
+            "Found DEX changes"
+        );
+        insert_dex_changes(self.connection_pool.clone(), changes).await
+    }
+}
+
+impl Debug for DexProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "DexProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+#[async_trait]
+impl ProcessorTrait for DexProcessor {
+    fn name(&self) -> &'static str {
+        ProcessorConfig::DexProcessor("".into()).name()
+    }
+
+    fn connection_pool(&self) -> &Arc<DbPool> {
+        &self.connection_pool
+    }
+
+    async fn process_blocks(
+        &self,
+        _from: i64,
+        _to: i64,
+        blocks: Vec<Vec<BlockAndEvents>>,
+    ) -> Result<()> {
+        self.insert(blocks.into_iter().flatten().collect()).await
+    }
+
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        delete_dex_changes_of_blocks(self.connection_pool.clone(), orphaned).await?;
+        self.insert(replacements).await
+    }
+}
+
+/// Returns the pairs created by the factory and the events of its pairs, `known_pairs` being
+/// the pairs created before these blocks. Blocks are expected to be on the main chain.
+pub fn convert_to_dex_changes(
+    blocks: &[BlockAndEvents],
+    factory_address: &str,
+    mut known_pairs: HashSet<String>,
+) -> DexChanges {
+    let mut blocks: Vec<&BlockAndEvents> = blocks.iter().collect();
+    blocks.sort_by_key(|be| be.block.timestamp);
+
+    let mut changes = DexChanges::default();
+    for be in blocks {
+        let timestamp = timestamp_millis_to_naive_datetime(be.block.timestamp);
+        for (event_order, event) in be.events.iter().enumerate() {
+            let event_order = event_order as i32;
+            let block_hash = be.block.hash.clone();
+            if event.contract_address == factory_address {
+                let Some(decoded) = decode_event(&FACTORY_ABI, event) else {
+                    continue;
+                };
+                if decoded.name != "PairCreated" {
+                    continue;
+                }
+                let Some(pair) = convert_to_pair(&decoded, factory_address, block_hash, timestamp)
+                else {
+                    tracing::warn!(tx_id = event.tx_id, "Invalid PairCreated event, skipping");
+                    continue;
+                };
+                known_pairs.insert(pair.address.clone());
+                changes.pairs.push(pair);
+            } else if known_pairs.contains(&event.contract_address) {
+                let Some(decoded) = decode_event(&PAIR_ABI, event) else {
+                    continue;
+                };
+                let valid = match decoded.name.as_str() {
+                    "Swap" => convert_to_swap(&decoded, block_hash, event_order, timestamp)
+                        .map(|swap| changes.swaps.push(swap)),
+                    "Mint" | "Burn" => {
+                        convert_to_liquidity_action(&decoded, block_hash, event_order, timestamp)
+                            .map(|action| changes.liquidity_actions.push(action))
+                    }
+                    "Sync" => convert_to_reserve(&decoded, block_hash, event_order, timestamp)
+                        .map(|reserve| changes.reserves.push(reserve)),
+                    _ => Some(()),
+                };
+                if valid.is_none() {
+                    tracing::warn!(
+                        tx_id = event.tx_id,
+                        event = decoded.name,
+                        "Invalid pair event, skipping"
+                    );
+                }
+            }
+        }
+    }
+    changes
+}
+
+/// Decodes an event of a DEX contract. System events, with no signature in the artifact, and
+/// events not matching their signature are skipped.
+fn decode_event(abi: &ContractAbi, event: &ContractEventByBlockHash) -> Option<DecodedEvent> {
+    match abi.decode_event(event) {
+        Ok(decoded) => Some(decoded),
+        Err(AbiError::UnknownEvent(_)) => None,
+        Err(err) => {
+            tracing::warn!(tx_id = event.tx_id, error = %err, "Invalid DEX event, skipping");
+            None
+        }
+    }
+}
+
+fn convert_to_pair(
+    event: &DecodedEvent,
+    factory_address: &str,
+    block_hash: String,
+    timestamp: NaiveDateTime,
+) -> Option<DexPairModel> {
+    Some(DexPairModel {
+        // The pair is given by its contract id
+        address: address_from_contract_id(str_field(event, "pair")?)?,
+        factory_address: factory_address.to_string(),
+        token0_id: str_field(event, "token0")?.to_string(),
+        token1_id: str_field(event, "token1")?.to_string(),
+        block_hash,
+        tx_id: event.tx_id.clone(),
+        timestamp,
+    })
+}
+
+fn convert_to_swap(
+    event: &DecodedEvent,
+    block_hash: String,
+    event_order: i32,
+    timestamp: NaiveDateTime,
+) -> Option<DexSwapModel> {
+    let amount0_in = number_field(event, "amount0In")?;
+    let amount1_in = number_field(event, "amount1In")?;
+    let amount0_out = number_field(event, "amount0Out")?;
+    let amount1_out = number_field(event, "amount1Out")?;
+    Some(DexSwapModel {
+        block_hash,
+        event_order,
+        pair_address: event.contract_address.clone(),
+        tx_id: event.tx_id.clone(),
+        trader: str_field(event, "sender")?.to_string(),
+        to_address: str_field(event, "to")?.to_string(),
+        price: swap_price(&amount0_in, &amount1_in, &amount0_out, &amount1_out),
+        amount0_in,
+        amount1_in,
+        amount0_out,
+        amount1_out,
+        timestamp,
+    })
+}
+
+fn convert_to_liquidity_action(
+    event: &DecodedEvent,
+    block_hash: String,
+    event_order: i32,
+    timestamp: NaiveDateTime,
+) -> Option<DexLiquidityActionModel> {
+    let action_type = match event.name.as_str() {
+        "Mint" => LiquidityActionType::Mint,
+        _ => LiquidityActionType::Burn,
+    };
+    Some(DexLiquidityActionModel {
+        block_hash,
+        event_order,
+        pair_address: event.contract_address.clone(),
+        tx_id: event.tx_id.clone(),
+        action_type,
+        sender: str_field(event, "sender")?.to_string(),
+        amount0: number_field(event, "amount0")?,
+        amount1: number_field(event, "amount1")?,
+        liquidity: number_field(event, "liquidity")?,
+        timestamp,
+    })
+}
+
+fn convert_to_reserve(
+    event: &DecodedEvent,
+    block_hash: String,
+    event_order: i32,
+    timestamp: NaiveDateTime,
+) -> Option<DexReserveModel> {
+    Some(DexReserveModel {
+        block_hash,
+        event_order,
+        pair_address: event.contract_address.clone(),
+        tx_id: event.tx_id.clone(),
+        reserve0: number_field(event, "reserve0")?,
+        reserve1: number_field(event, "reserve1")?,
+        timestamp,
+    })
+}
+
+fn str_field<'a>(event: &'a DecodedEvent, name: &str) -> Option<&'a str> {
+    event.field(name).and_then(AbiValue::as_str)
+}
+
+fn number_field(event: &DecodedEvent, name: &str) -> Option<BigDecimal> {
+    event.field(name).and_then(AbiValue::as_number).cloned()
+}
+
+/// Returns the amount of token1 exchanged per unit of token0 in a swap, if any token0 was
+/// exchanged.
+pub fn swap_price(
+    amount0_in: &BigDecimal,
+    amount1_in: &BigDecimal,
+    amount0_out: &BigDecimal,
+    amount1_out: &BigDecimal,
+) -> Option<BigDecimal> {
+    let amount0 = amount0_in + amount0_out;
+    let amount1 = amount1_in + amount1_out;
+    if amount0.is_zero() {
+        return None;
+    }
+    Some((amount1 / amount0).with_prec(PRICE_PRECISION).normalized())
+}
+
+/// Returns the pairs of a factory among a list of contract addresses.
+pub async fn get_known_pairs(
+    db: Arc<DbPool>,
+    factory_address: &str,
+    addresses: &[String],
+) -> Result<HashSet<String>> {
+    use crate::schema::dex_pairs;
+
+    if addresses.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let mut conn = db.get().await?;
+    let pairs: Vec<String> = dex_pairs::table
+        .filter(dex_pairs::factory_address.eq(factory_address))
+        .filter(dex_pairs::address.eq_any(addresses))
+        .select(dex_pairs::address)
+        .load(&mut conn)
+        .await?;
+    Ok(pairs.into_iter().collect())
+}
+
+/// Insert pairs, swaps, liquidity actions and reserves in a single DB transaction.
+pub async fn insert_dex_changes(db: Arc<DbPool>, changes: DexChanges) -> Result<()> {
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            insert_into(crate::schema::dex_pairs::table)
+                .values(&changes.pairs)
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await?;
+            insert_in_chunks(conn, &changes.swaps, |chunk| {
+                insert_into(crate::schema::dex_swaps::table).values(chunk).on_conflict_do_nothing()
+            })
+            .await?;
+            insert_in_chunks(conn, &changes.liquidity_actions, |chunk| {
+                insert_into(crate::schema::dex_liquidity_actions::table)
+                    .values(chunk)
+                    .on_conflict_do_nothing()
+            })
+            .await?;
+            insert_in_chunks(conn, &changes.reserves, |chunk| {
+                insert_into(crate::schema::dex_reserves::table)
+                    .values(chunk)
+                    .on_conflict_do_nothing()
+            })
+            .await?;
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Delete the pairs, swaps, liquidity actions and reserves derived from a list of blocks.
+pub async fn delete_dex_changes_of_blocks(
+    db: Arc<DbPool>,
+    block_hashes: &[BlockHash],
+) -> Result<()> {
+    use crate::schema::{dex_liquidity_actions, dex_pairs, dex_reserves, dex_swaps};
+
+    if block_hashes.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(dex_pairs::table.filter(dex_pairs::block_hash.eq_any(block_hashes)))
+                .execute(conn)
+                .await?;
+            diesel::delete(dex_swaps::table.filter(dex_swaps::block_hash.eq_any(block_hashes)))
+                .execute(conn)
+                .await?;
+            diesel::delete(
+                dex_liquidity_actions::table
+                    .filter(dex_liquidity_actions::block_hash.eq_any(block_hashes)),
+            )
+            .execute(conn)
+            .await?;
+            diesel::delete(
+                dex_reserves::table.filter(dex_reserves::block_hash.eq_any(block_hashes)),
+            )
+            .execute(conn)
+            .await?;
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Returns the latest reserves of a pair.
+pub async fn get_latest_reserves(
+    db: Arc<DbPool>,
+    pair_address: &str,
+) -> Result<Option<DexReserveModel>> {
+    use crate::schema::dex_reserves;
+
+    let mut conn = db.get().await?;
+    let reserves = dex_reserves::table
+        .filter(dex_reserves::pair_address.eq(pair_address))
+        .order_by((dex_reserves::timestamp.desc(), dex_reserves::event_order.desc()))
+        .select(DexReserveModel::as_select())
+        .first(&mut conn)
+        .await
+        .optional()?;
+    Ok(reserves)
+}
+
+/// Returns the swaps of a pair within a time range, oldest first.
+pub async fn get_swaps(
+    db: Arc<DbPool>,
+    pair_address: &str,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Vec<DexSwapModel>> {
+    use crate::schema::dex_swaps;
+
+    let mut conn = db.get().await?;
+    let swaps = dex_swaps::table
+        .filter(dex_swaps::pair_address.eq(pair_address))
+        .filter(dex_swaps::timestamp.between(from, to))
+        .order_by((dex_swaps::timestamp, dex_swaps::event_order))
+        .select(DexSwapModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(swaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Client, Network};
+    use crate::test_utils::block_and_events;
+    use crate::types::ContractEvent;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    const FACTORY: &str = "factory";
+    const PAIR_ID: &str = "25469eb0d0d0a55deea832924547b7b166c70a3554fe321e81886d3c18f19d64";
+    const PAIR: &str = "wCTeteGBeSEC54GpkS8jWBzYiYNTBUuTW3WzxGd9yExT";
+    const TRADER: &str = "1FQuaJLe6BAcHYoQdhW2TMENVpRgGS8rpMtLRsy9ZjbPq";
+
+    fn block(hash: &str, timestamp: i64, events: serde_json::Value) -> BlockAndEvents {
+        block_and_events(hash, true, timestamp, json!([]), events)
+    }
+
+    fn u256(value: &str) -> serde_json::Value {
+        json!({ "type": "U256", "value": value })
+    }
+
+    fn pair_event(name: &str) -> i32 {
+        PAIR_ABI.event_index(name).unwrap()
+    }
+
+    #[test]
+    fn test_convert_to_dex_changes() {
+        let address = json!({ "type": "Address", "value": TRADER });
+        let blocks = vec![
+            block(
+                "swap",
+                1735689600001,
+                json!([
+                    {
+                        "txId": "swap",
+                        "contractAddress": PAIR,
+                        "eventIndex": pair_event("Swap"),
+                        "fields": [address, u256("1000"), u256("0"), u256("0"), u256("1995"), address]
+                    },
+                    {
+                        "txId": "swap",
+                        "contractAddress": PAIR,
+                        "eventIndex": pair_event("Sync"),
+                        "fields": [u256("11000"), u256("20005")]
+                    },
+                    {
+                        "txId": "swap",
+                        "contractAddress": "unknown_pair",
+                        "eventIndex": pair_event("Sync"),
+                        "fields": [u256("1"), u256("1")]
+                    }
+                ]),
+            ),
+            block(
+                "create",
+                1735689600000,
+                json!([
+                    {
+                        "txId": "create",
+                        "contractAddress": FACTORY,
+                        "eventIndex": FACTORY_ABI.event_index("PairCreated").unwrap(),
+                        "fields": [
+                            { "type": "ByteVec", "value": "token0" },
+                            { "type": "ByteVec", "value": "token1" },
+                            { "type": "ByteVec", "value": PAIR_ID },
+                            u256("1")
+                        ]
+                    },
+                    {
+                        "txId": "create",
+                        "contractAddress": PAIR,
+                        "eventIndex": pair_event("Mint"),
+                        "fields": [address, u256("10000"), u256("22000"), u256("14832")]
+                    },
+                    {
+                        "txId": "create",
+                        "contractAddress": PAIR,
+                        "eventIndex": pair_event("Burn"),
+                        "fields": [address]
+                    }
+                ]),
+            ),
+        ];
+
+        // The pair is discovered from the factory event of the earlier block
+        let changes = convert_to_dex_changes(&blocks, FACTORY, HashSet::new());
+        assert_eq!(changes.pairs.len(), 1);
+        assert_eq!(changes.pairs[0].address, PAIR);
+        assert_eq!(changes.pairs[0].token0_id, "token0");
+
+        assert_eq!(changes.liquidity_actions.len(), 1);
+        let mint = &changes.liquidity_actions[0];
+        assert_eq!(mint.action_type, LiquidityActionType::Mint);
+        assert_eq!(mint.liquidity, BigDecimal::from(14832));
+
+        assert_eq!(changes.swaps.len(), 1);
+        let swap = &changes.swaps[0];
+        assert_eq!((swap.trader.as_str(), swap.event_order), (TRADER, 0));
+        assert_eq!(swap.amount1_out, BigDecimal::from(1995));
+        assert_eq!(swap.price, Some(BigDecimal::from_str("1.995").unwrap()));
+
+        let reserves: Vec<_> =
+            changes.reserves.iter().map(|r| (r.reserve0.clone(), r.reserve1.clone())).collect();
+        assert_eq!(reserves, vec![(BigDecimal::from(11000), BigDecimal::from(20005))]);
+
+        // Events of pairs known from previous blocks are kept without their creation
+        let changes = convert_to_dex_changes(&blocks[..1], FACTORY, HashSet::from([PAIR.into()]));
+        assert_eq!((changes.swaps.len(), changes.reserves.len()), (1, 1));
+        assert_eq!(
+            convert_to_dex_changes(&blocks[..1], FACTORY, HashSet::new()),
+            DexChanges::default()
+        );
+    }
+
+    #[test]
+    fn test_dex_artifacts() {
+        assert_eq!(FACTORY_ABI.event_index("PairCreated"), Some(0));
+        for (index, name) in ["Mint", "Burn", "Swap", "Sync"].iter().enumerate() {
+            assert_eq!(PAIR_ABI.event_index(name), Some(index as i32));
+        }
+    }
+
+    /// Checks the artifacts against the DEX deployed on mainnet at `DEX_FACTORY_ADDRESS`: the
+    /// factory and its first pair must run the code of the artifacts, and the first event of
+    /// the factory must decode as `PairCreated`. Needs a mainnet node, `MAINNET_NODE_URL`
+    /// selects another one than the public node.
+    #[tokio::test]
+    #[ignore = "needs a mainnet node"]
+    async fn test_dex_artifacts_match_mainnet() {
+        let factory = std::env::var("DEX_FACTORY_ADDRESS").expect("DEX_FACTORY_ADDRESS not set");
+        let client = Client::new(Network::Mainnet).unwrap();
+        let factory_state = client.get_contract_state(&factory).await.unwrap();
+        assert_eq!(FACTORY_ABI.code_hash.as_ref(), Some(&factory_state.code_hash));
+
+        let page = client.get_contract_events(&factory, 0, Some(1)).await.unwrap();
+        let ContractEvent { tx_id, event_index, fields, .. } =
+            page.events.into_iter().next().expect("No pair created");
+        let event =
+            ContractEventByBlockHash { tx_id, contract_address: factory, event_index, fields };
+        let pair_created = FACTORY_ABI.decode_event(&event).unwrap();
+        assert_eq!(pair_created.name, "PairCreated");
+        let pair = address_from_contract_id(str_field(&pair_created, "pair").unwrap()).unwrap();
+        let pair_state = client.get_contract_state(&pair).await.unwrap();
+        assert_eq!(PAIR_ABI.code_hash.as_ref(), Some(&pair_state.code_hash));
+    }
+
+    #[test]
+    fn test_swap_price() {
+        let amount = |value: i64| BigDecimal::from(value);
+        assert_eq!(swap_price(&amount(0), &amount(300), &amount(100), &amount(0)), Some(amount(3)));
+        assert_eq!(
+            swap_price(&amount(3), &amount(0), &amount(0), &amount(1)),
+            Some(BigDecimal::from_str("0.333333333333333333333333333333").unwrap())
+        );
+        assert_eq!(swap_price(&amount(0), &amount(1), &amount(0), &amount(1)), None);
+    }
+}
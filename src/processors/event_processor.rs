@@ -5,8 +5,11 @@ use anyhow::Result;
 use async_trait::async_trait;
 
 use crate::{
-    config::ProcessorConfig, db::DbPool, models::convert_bwe_to_event_models,
-    repository::insert_events_to_db, types::BlockAndEvents,
+    config::ProcessorConfig,
+    db::DbPool,
+    models::convert_bwe_to_event_models,
+    repository::{insert_events_to_db, update_events_main_chain_status},
+    types::{BlockAndEvents, BlockHash},
 };
 
 use super::ProcessorTrait;
@@ -60,4 +63,21 @@ impl ProcessorTrait for EventProcessor {
         }
         Ok(())
     }
+
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        // Keep the events of orphaned blocks, marked as such
+        update_events_main_chain_status(self.connection_pool.clone(), orphaned, false).await?;
+
+        // Events of replacements may have been stored while they were not in the main chain
+        let hashes: Vec<BlockHash> = replacements.iter().map(|be| be.block.hash.clone()).collect();
+        let models = convert_bwe_to_event_models(vec![replacements]);
+        if !models.is_empty() {
+            insert_events_to_db(self.connection_pool.clone(), models).await?;
+        }
+        update_events_main_chain_status(self.connection_pool.clone(), &hashes, true).await
+    }
 }
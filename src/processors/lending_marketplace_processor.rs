@@ -2,8 +2,9 @@ use std::fmt::Debug;
 use std::sync::Arc;
 
 use crate::config::ProcessorConfig;
+use crate::models::error::CustomError;
 use crate::processors::ProcessorTrait;
-use crate::types::ContractEventByBlockHash;
+use crate::types::{BlockHash, ContractEventByBlockHash};
 use crate::utils::timestamp_millis_to_naive_datetime;
 use crate::{db::DbPool, types::BlockAndEvents};
 use anyhow::Result;
@@ -29,6 +30,7 @@ pub struct LoanActionModel {
     by: String,
     timestamp: NaiveDateTime,
     action_type: LoanActionType,
+    block_hash: String,
 }
 
 #[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize, AsChangeset)]
@@ -43,6 +45,7 @@ pub struct LoanDetailModel {
     interest_rate: BigDecimal,
     duration: BigDecimal,
     lender: String,
+    block_hash: String,
 }
 
 pub struct LendingContractProcessor {
@@ -93,6 +96,23 @@ impl ProcessorTrait for LendingContractProcessor {
         }
         Ok(())
     }
+
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        delete_loans_of_blocks(self.connection_pool.clone(), orphaned).await?;
+        let (loan_actions, loan_details) =
+            convert_to_model(vec![replacements], &self.contract_address);
+        if !loan_actions.is_empty() {
+            insert_loan_actions_to_db(self.connection_pool.clone(), loan_actions).await?;
+        }
+        if !loan_details.is_empty() {
+            insert_loan_details_to_db(self.connection_pool.clone(), loan_details).await?;
+        }
+        Ok(())
+    }
 }
 
 /// Insert loan actions into the database.
@@ -101,7 +121,11 @@ pub async fn insert_loan_actions_to_db(
     actions: Vec<LoanActionModel>,
 ) -> Result<()> {
     let mut conn = db.get().await?;
-    insert_into(crate::schema::loan_actions::table).values(&actions).execute(&mut conn).await?;
+    insert_into(crate::schema::loan_actions::table)
+        .values(&actions)
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await?;
     Ok(())
 }
 
@@ -111,7 +135,32 @@ pub async fn insert_loan_details_to_db(
     details: Vec<LoanDetailModel>,
 ) -> Result<()> {
     let mut conn = db.get().await?;
-    insert_into(crate::schema::loan_details::table).values(&details).execute(&mut conn).await?;
+    insert_into(crate::schema::loan_details::table)
+        .values(&details)
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Delete the loan actions and details derived from a list of blocks.
+pub async fn delete_loans_of_blocks(db: Arc<DbPool>, block_hashes: &[BlockHash]) -> Result<()> {
+    if block_hashes.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    diesel::delete(
+        crate::schema::loan_actions::table
+            .filter(crate::schema::loan_actions::block_hash.eq_any(block_hashes)),
+    )
+    .execute(&mut conn)
+    .await?;
+    diesel::delete(
+        crate::schema::loan_details::table
+            .filter(crate::schema::loan_details::block_hash.eq_any(block_hashes)),
+    )
+    .execute(&mut conn)
+    .await?;
     Ok(())
 }
 
@@ -123,13 +172,13 @@ pub fn convert_to_model(
     let mut loan_details = Vec::new();
     for bes in blocks {
         for be in bes {
-            let events = be.events;
-            for event in events {
+            let block_hash = &be.block.hash;
+            for event in be.events {
                 if event.contract_address.eq(&contract_address) {
                     if let Some(action) = LoanActionType::from_event_index(event.event_index) {
-                        handle_loan_action_event(&mut loan_actions, &event, action);
+                        handle_loan_action_event(&mut loan_actions, &event, action, block_hash);
                     } else if event.event_index == 1 {
-                        handle_loan_detail_event(&event, &mut loan_details);
+                        handle_loan_detail_event(&event, &mut loan_details, block_hash);
                     }
                 }
             }
@@ -138,19 +187,6 @@ pub fn convert_to_model(
     (loan_actions, loan_details)
 }
 
-#[derive(Debug, thiserror::Error)]
-#[error("CustomError: {msg}, {status}")]
-pub struct CustomError {
-    msg: String,
-    status: u16,
-}
-
-impl CustomError {
-    fn not_found(msg: String) -> Self {
-        Self { msg, status: 404 }
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, FromSqlRow, DbEnum, Serialize, AsExpression)]
 #[diesel(sql_type = SmallInt)]
 #[diesel_enum(error_fn = CustomError::not_found)]
@@ -190,6 +226,7 @@ fn handle_loan_action_event(
     models: &mut Vec<LoanActionModel>,
     event: &ContractEventByBlockHash,
     action: LoanActionType,
+    block_hash: &str,
 ) {
     // Sanity check
     if event.fields.len() < 3 {
@@ -199,48 +236,62 @@ fn handle_loan_action_event(
     match action {
         LoanActionType::LoanCreated => {
             models.push(LoanActionModel {
-                loan_subcontract_id: event.fields[0].value.clone(),
+                loan_subcontract_id: event.fields[0].value_string(),
 
                 action_type: action,
-                by: event.fields[2].value.clone(),
+                by: event.fields[2].value_string(),
                 timestamp: timestamp_millis_to_naive_datetime(
-                    event.fields[3].value.parse::<i64>().unwrap(),
+                    event.fields[3].value_string().parse::<i64>().unwrap(),
                 ),
                 loan_id: Some(
-                    BigDecimal::from_f64(event.fields[1].value.parse::<f64>().unwrap()).unwrap(),
+                    BigDecimal::from_f64(event.fields[1].value_string().parse::<f64>().unwrap())
+                        .unwrap(),
                 ),
+                block_hash: block_hash.to_string(),
             });
         }
         _ => {
             models.push(LoanActionModel {
-                loan_subcontract_id: event.fields[0].value.clone(),
+                loan_subcontract_id: event.fields[0].value_string(),
                 action_type: action,
-                by: event.fields[1].value.clone(),
+                by: event.fields[1].value_string(),
                 timestamp: timestamp_millis_to_naive_datetime(
-                    event.fields[2].value.parse::<i64>().unwrap(),
+                    event.fields[2].value_string().parse::<i64>().unwrap(),
                 ),
                 loan_id: None, // Other actions does not need this field
+                block_hash: block_hash.to_string(),
             });
         }
     }
 }
 
-fn handle_loan_detail_event(event: &ContractEventByBlockHash, models: &mut Vec<LoanDetailModel>) {
+fn handle_loan_detail_event(
+    event: &ContractEventByBlockHash,
+    models: &mut Vec<LoanDetailModel>,
+    block_hash: &str,
+) {
     // Sanity check
     if event.fields.len() != 8 {
         tracing::warn!("Invalid event fields length: {}, skipping", event.fields.len());
     }
 
     models.push(LoanDetailModel {
-        loan_subcontract_id: event.fields[0].value.clone(),
-        lending_token_id: event.fields[1].value.clone(),
-        collateral_token_id: event.fields[2].value.clone(),
-        lending_amount: BigDecimal::from_f64(event.fields[3].value.parse::<f64>().unwrap())
+        loan_subcontract_id: event.fields[0].value_string(),
+        lending_token_id: event.fields[1].value_string(),
+        collateral_token_id: event.fields[2].value_string(),
+        lending_amount: BigDecimal::from_f64(
+            event.fields[3].value_string().parse::<f64>().unwrap(),
+        )
+        .unwrap(),
+        collateral_amount: BigDecimal::from_f64(
+            event.fields[4].value_string().parse::<f64>().unwrap(),
+        )
+        .unwrap(),
+        interest_rate: BigDecimal::from_f64(event.fields[5].value_string().parse::<f64>().unwrap())
             .unwrap(),
-        collateral_amount: BigDecimal::from_f64(event.fields[4].value.parse::<f64>().unwrap())
+        duration: BigDecimal::from_f64(event.fields[6].value_string().parse::<f64>().unwrap())
             .unwrap(),
-        interest_rate: BigDecimal::from_f64(event.fields[5].value.parse::<f64>().unwrap()).unwrap(),
-        duration: BigDecimal::from_f64(event.fields[6].value.parse::<f64>().unwrap()).unwrap(),
-        lender: event.fields[7].value.clone(),
+        lender: event.fields[7].value_string(),
+        block_hash: block_hash.to_string(),
     });
 }
@@ -1,20 +1,34 @@
 use crate::{
     db::{DbPool, DbPoolConnection},
-    types::BlockAndEvents,
+    types::{BlockAndEvents, BlockHash},
 };
 use anyhow::Result;
 use async_trait::async_trait;
 use block_processor::BlockProcessor;
+use contract_processor::ContractProcessor;
 use default_processor::DefaultProcessor;
+use dex_processor::DexProcessor;
 use diesel_async::{pooled_connection::bb8::Pool, AsyncPgConnection};
 use event_processor::EventProcessor;
 use lending_marketplace_processor::LendingContractProcessor;
+use nft_processor::NftProcessor;
 use std::{fmt::Debug, sync::Arc};
+use token_registry_processor::TokenRegistryProcessor;
+use token_transfer_processor::TokenTransferProcessor;
+use transaction_processor::TransactionProcessor;
+use utxo_processor::UtxoProcessor;
 
 pub mod block_processor;
+pub mod contract_processor;
 pub mod default_processor;
+pub mod dex_processor;
 pub mod event_processor;
 pub mod lending_marketplace_processor;
+pub mod nft_processor;
+pub mod token_registry_processor;
+pub mod token_transfer_processor;
+pub mod transaction_processor;
+pub mod utxo_processor;
 
 /// Base trait for all processors
 #[async_trait]
@@ -50,6 +64,25 @@ pub trait ProcessorTrait: Send + Sync + Debug {
         to_ts: i64,
         blocks: Vec<Vec<BlockAndEvents>>,
     ) -> Result<()>;
+
+    /// Called when a reorg is detected, before the window that triggered it is processed.
+    ///
+    /// `orphaned` are the blocks that left the main chain, whose derived rows should be deleted
+    /// or marked as orphaned. `replacements` are the blocks that joined the main chain in their
+    /// place and should be (re-)applied. By default, nothing is reverted and the replacements
+    /// are processed like any other block.
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        let _ = orphaned;
+        let timestamps = replacements.iter().map(|be| be.block.timestamp);
+        let (Some(from_ts), Some(to_ts)) = (timestamps.clone().min(), timestamps.max()) else {
+            return Ok(());
+        };
+        self.process_blocks(from_ts, to_ts, vec![replacements]).await
+    }
 }
 
 #[derive(Debug)]
@@ -58,6 +91,13 @@ pub enum Processor {
     DefaultProcessor(DefaultProcessor),
     EventProcessor(EventProcessor),
     LendingContractProcessor(LendingContractProcessor),
+    TransactionProcessor(TransactionProcessor),
+    UtxoProcessor(UtxoProcessor),
+    TokenTransferProcessor(TokenTransferProcessor),
+    ContractProcessor(ContractProcessor),
+    TokenRegistryProcessor(TokenRegistryProcessor),
+    NftProcessor(NftProcessor),
+    DexProcessor(DexProcessor),
 }
 
 #[async_trait]
@@ -68,6 +108,13 @@ impl ProcessorTrait for Processor {
             Processor::BlockProcessor(p) => p.connection_pool(),
             Processor::EventProcessor(p) => p.connection_pool(),
             Processor::LendingContractProcessor(p) => p.connection_pool(),
+            Processor::TransactionProcessor(p) => p.connection_pool(),
+            Processor::UtxoProcessor(p) => p.connection_pool(),
+            Processor::TokenTransferProcessor(p) => p.connection_pool(),
+            Processor::ContractProcessor(p) => p.connection_pool(),
+            Processor::TokenRegistryProcessor(p) => p.connection_pool(),
+            Processor::NftProcessor(p) => p.connection_pool(),
+            Processor::DexProcessor(p) => p.connection_pool(),
         }
     }
 
@@ -77,6 +124,13 @@ impl ProcessorTrait for Processor {
             Processor::BlockProcessor(p) => p.name(),
             Processor::EventProcessor(p) => p.name(),
             Processor::LendingContractProcessor(p) => p.name(),
+            Processor::TransactionProcessor(p) => p.name(),
+            Processor::UtxoProcessor(p) => p.name(),
+            Processor::TokenTransferProcessor(p) => p.name(),
+            Processor::ContractProcessor(p) => p.name(),
+            Processor::TokenRegistryProcessor(p) => p.name(),
+            Processor::NftProcessor(p) => p.name(),
+            Processor::DexProcessor(p) => p.name(),
         }
     }
 
@@ -93,6 +147,33 @@ impl ProcessorTrait for Processor {
             Processor::LendingContractProcessor(p) => {
                 p.process_blocks(from_ts, to_ts, blocks).await
             }
+            Processor::TransactionProcessor(p) => p.process_blocks(from_ts, to_ts, blocks).await,
+            Processor::UtxoProcessor(p) => p.process_blocks(from_ts, to_ts, blocks).await,
+            Processor::TokenTransferProcessor(p) => p.process_blocks(from_ts, to_ts, blocks).await,
+            Processor::ContractProcessor(p) => p.process_blocks(from_ts, to_ts, blocks).await,
+            Processor::TokenRegistryProcessor(p) => p.process_blocks(from_ts, to_ts, blocks).await,
+            Processor::NftProcessor(p) => p.process_blocks(from_ts, to_ts, blocks).await,
+            Processor::DexProcessor(p) => p.process_blocks(from_ts, to_ts, blocks).await,
+        }
+    }
+
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        match self {
+            Processor::DefaultProcessor(p) => p.handle_reorg(orphaned, replacements).await,
+            Processor::BlockProcessor(p) => p.handle_reorg(orphaned, replacements).await,
+            Processor::EventProcessor(p) => p.handle_reorg(orphaned, replacements).await,
+            Processor::LendingContractProcessor(p) => p.handle_reorg(orphaned, replacements).await,
+            Processor::TransactionProcessor(p) => p.handle_reorg(orphaned, replacements).await,
+            Processor::UtxoProcessor(p) => p.handle_reorg(orphaned, replacements).await,
+            Processor::TokenTransferProcessor(p) => p.handle_reorg(orphaned, replacements).await,
+            Processor::ContractProcessor(p) => p.handle_reorg(orphaned, replacements).await,
+            Processor::TokenRegistryProcessor(p) => p.handle_reorg(orphaned, replacements).await,
+            Processor::NftProcessor(p) => p.handle_reorg(orphaned, replacements).await,
+            Processor::DexProcessor(p) => p.handle_reorg(orphaned, replacements).await,
         }
     }
 }
@@ -0,0 +1,631 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use diesel::pg::sql_types::Array;
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use diesel::{insert_into, sql_query};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use futures::{StreamExt, TryStreamExt};
+use serde::Serialize;
+
+use crate::{
+    client::Client,
+    config::ProcessorConfig,
+    db::DbPool,
+    processors::{
+        contract_processor::{convert_to_contract_changes, ContractModel},
+        token_registry_processor::utf8_from_hex,
+        ProcessorTrait,
+    },
+    repository::insert_in_chunks,
+    types::{BlockAndEvents, BlockHash, CallContractResult, EventField, EventFieldType},
+    utils::timestamp_millis_to_naive_datetime,
+};
+
+/// Prefix of the standard interface ids, which the node may or may not strip in contract
+/// creation events.
+const STD_INTERFACE_ID_PREFIX: &str = "414c5048";
+/// Interface id of NFT collections. Collections with royalties extend it.
+pub const NFT_COLLECTION_INTERFACE_ID: &str = "0002";
+/// Interface id of NFTs.
+pub const NFT_INTERFACE_ID: &str = "0003";
+
+/// Method indexes of the NFT collection standard: `getCollectionUri` and `totalSupply`.
+pub const NFT_COLLECTION_METHODS: [i32; 2] = [0, 1];
+/// Method indexes of the NFT standard: `getTokenUri` and `getCollectionIndex`.
+pub const NFT_METHODS: [i32; 2] = [0, 1];
+
+/// Maximum number of contracts whose metadata is requested from the node at once.
+const METADATA_FETCH_CONCURRENCY: usize = 8;
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::nft_collections)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NftCollectionModel {
+    pub address: String,
+    pub contract_id: String,
+    pub std_interface_id: String,
+    pub collection_uri: Option<String>,
+    pub total_supply: Option<BigDecimal>,
+    pub creation_block_hash: String,
+    pub creation_tx_id: String,
+    pub creation_timestamp: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::nfts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NftModel {
+    pub token_id: String,
+    pub address: String,
+    pub collection_address: Option<String>,
+    pub nft_index: Option<BigDecimal>,
+    pub token_uri: Option<String>,
+    pub minted_block_hash: String,
+    pub minted_tx_id: String,
+    pub minted_timestamp: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::nft_movements)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NftMovementModel {
+    pub token_id: String,
+    pub block_hash: String,
+    pub tx_id: String,
+    pub tx_index: i32,
+    pub to_address: String,
+    pub timestamp: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::nft_owners)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NftOwnerModel {
+    pub token_id: String,
+    pub owner_address: String,
+    pub block_hash: String,
+    pub tx_id: String,
+    pub timestamp: NaiveDateTime,
+}
+
+/// NFT collections created and NFTs minted in a batch of blocks, before their metadata is
+/// fetched.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NftMints {
+    pub collections: Vec<NftCollectionModel>,
+    pub nfts: Vec<NftModel>,
+}
+
+/// Indexes the NFT collections and NFTs following the Alephium NFT standard, and the current
+/// owner of every NFT.
+///
+/// Collections and NFTs are recognized by the standard interface id of the contracts created in
+/// main chain transactions, their URIs being read once through
+/// `/contracts/multicall-contract`. Every output an NFT is sent to is recorded, and the owner
+/// of an NFT is the address of the latest one. NFTs burnt without being sent to another output
+/// keep their last owner.
+pub struct NftProcessor {
+    connection_pool: Arc<DbPool>,
+    client: Arc<Client>,
+    group_num: i64,
+}
+
+impl NftProcessor {
+    pub fn new(connection_pool: Arc<DbPool>, client: Arc<Client>, group_num: i64) -> Self {
+        Self { connection_pool, client, group_num }
+    }
+
+    async fn insert(&self, blocks: Vec<BlockAndEvents>) -> Result<()> {
+        let mut mints = convert_to_nft_mints(blocks.iter().cloned());
+
+        // Movements of the NFTs minted in these blocks or known from previous ones
+        let mut nft_ids: HashSet<String> =
+            mints.nfts.iter().map(|nft| nft.token_id.clone()).collect();
+        let token_ids: Vec<String> = output_token_ids(&blocks).into_iter().collect();
+        nft_ids.extend(get_known_nft_ids(self.connection_pool.clone(), &token_ids).await?);
+        let movements = convert_to_nft_movements(&blocks, &nft_ids);
+        if mints.collections.is_empty() && mints.nfts.is_empty() && movements.is_empty() {
+            return Ok(());
+        }
+
+        mints.collections = futures::stream::iter(mints.collections)
+            .map(|collection| {
+                let client = self.client.clone();
+                let group_num = self.group_num;
+                async move {
+                    let results = client
+                        .call_contract_methods(
+                            &collection.contract_id,
+                            &NFT_COLLECTION_METHODS,
+                            group_num,
+                        )
+                        .await?;
+                    anyhow::Ok(with_collection_metadata(collection, &results))
+                }
+            })
+            .buffered(METADATA_FETCH_CONCURRENCY)
+            .try_collect()
+            .await?;
+        mints.nfts = futures::stream::iter(mints.nfts)
+            .map(|nft| {
+                let client = self.client.clone();
+                let group_num = self.group_num;
+                async move {
+                    let results = client
+                        .call_contract_methods(&nft.token_id, &NFT_METHODS, group_num)
+                        .await?;
+                    anyhow::Ok(with_nft_metadata(nft, &results))
+                }
+            })
+            .buffered(METADATA_FETCH_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        tracing::info!(
+            processor_name = ?self.name(),
+            collections = ?mints.collections.len(),
+            nfts = ?mints.nfts.len(),
+            movements = ?movements.len(),
+            "Found NFT changes"
+        );
+        insert_nft_changes(self.connection_pool.clone(), mints, movements).await
+    }
+}
+
+impl Debug for NftProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "NftProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+#[async_trait]
+impl ProcessorTrait for NftProcessor {
+    fn name(&self) -> &'static str {
+        ProcessorConfig::NftProcessor.name()
+    }
+
+    fn connection_pool(&self) -> &Arc<DbPool> {
+        &self.connection_pool
+    }
+
+    async fn process_blocks(
+        &self,
+        _from: i64,
+        _to: i64,
+        blocks: Vec<Vec<BlockAndEvents>>,
+    ) -> Result<()> {
+        self.insert(blocks.into_iter().flatten().collect()).await
+    }
+
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        revert_nft_changes(self.connection_pool.clone(), orphaned).await?;
+        self.insert(replacements).await
+    }
+}
+
+/// Returns the standard interface id without its prefix.
+fn strip_std_interface_prefix(std_interface_id: &str) -> &str {
+    std_interface_id.strip_prefix(STD_INTERFACE_ID_PREFIX).unwrap_or(std_interface_id)
+}
+
+/// Returns the NFT collections and NFTs created by the transactions of the main chain blocks,
+/// without their metadata.
+pub fn convert_to_nft_mints(blocks: impl IntoIterator<Item = BlockAndEvents>) -> NftMints {
+    let mut mints = NftMints::default();
+    for contract in convert_to_contract_changes(blocks).created {
+        let ContractModel { std_interface_id: Some(std_interface_id), .. } = &contract else {
+            continue;
+        };
+        let std_interface_id = strip_std_interface_prefix(std_interface_id);
+        if std_interface_id.starts_with(NFT_COLLECTION_INTERFACE_ID) {
+            mints.collections.push(NftCollectionModel {
+                std_interface_id: std_interface_id.to_string(),
+                address: contract.address,
+                contract_id: contract.contract_id,
+                collection_uri: None,
+                total_supply: None,
+                creation_block_hash: contract.creation_block_hash,
+                creation_tx_id: contract.creation_tx_id,
+                creation_timestamp: contract.creation_timestamp,
+            });
+        } else if std_interface_id.starts_with(NFT_INTERFACE_ID) {
+            mints.nfts.push(NftModel {
+                token_id: contract.contract_id,
+                address: contract.address,
+                collection_address: contract.parent_address,
+                nft_index: None,
+                token_uri: None,
+                minted_block_hash: contract.creation_block_hash,
+                minted_tx_id: contract.creation_tx_id,
+                minted_timestamp: contract.creation_timestamp,
+            });
+        }
+    }
+    mints
+}
+
+/// Returns the ids of the tokens held by the outputs of the main chain blocks.
+fn output_token_ids(blocks: &[BlockAndEvents]) -> HashSet<String> {
+    blocks
+        .iter()
+        .filter(|be| be.block.main_chain)
+        .flat_map(|be| be.block.transactions.iter())
+        .flat_map(|tx| {
+            let fixed = tx.unsigned.fixed_outputs.iter().flat_map(|output| output.tokens.iter());
+            let generated = tx.generated_outputs.iter().flat_map(|output| output.tokens().iter());
+            fixed.chain(generated).map(|token| token.id.clone())
+        })
+        .collect()
+}
+
+/// Returns the outputs of the main chain blocks holding one of the given NFTs.
+pub fn convert_to_nft_movements(
+    blocks: &[BlockAndEvents],
+    nft_ids: &HashSet<String>,
+) -> Vec<NftMovementModel> {
+    let mut movements = Vec::new();
+    for be in blocks.iter().filter(|be| be.block.main_chain) {
+        let timestamp = timestamp_millis_to_naive_datetime(be.block.timestamp);
+        for (tx_index, tx) in be.block.transactions.iter().enumerate() {
+            let fixed = tx
+                .unsigned
+                .fixed_outputs
+                .iter()
+                .map(|output| (output.address.as_str(), output.tokens.as_slice()));
+            let generated =
+                tx.generated_outputs.iter().map(|output| (output.address(), output.tokens()));
+            for (address, tokens) in fixed.chain(generated) {
+                for token in tokens.iter().filter(|token| nft_ids.contains(&token.id)) {
+                    movements.push(NftMovementModel {
+                        token_id: token.id.clone(),
+                        block_hash: be.block.hash.clone(),
+                        tx_id: tx.unsigned.tx_id.clone(),
+                        tx_index: tx_index as i32,
+                        to_address: address.to_string(),
+                        timestamp,
+                    });
+                }
+            }
+        }
+    }
+    movements
+}
+
+/// Returns the values returned by a successful call, if they have the expected types.
+fn returned<'a>(
+    result: Option<&'a CallContractResult>,
+    field_types: &[EventFieldType],
+) -> Option<Vec<&'a str>> {
+    let returns = result?.returns()?;
+    let types_match = returns.len() == field_types.len()
+        && returns.iter().zip(field_types).all(|(field, t)| field.field_type == *t);
+    if !types_match {
+        return None;
+    }
+    returns.iter().map(|EventField { value, .. }| value.as_str()).collect()
+}
+
+/// Fills the URI and total supply of a collection from the results of `NFT_COLLECTION_METHODS`,
+/// leaving the ones that couldn't be read empty.
+pub fn with_collection_metadata(
+    mut collection: NftCollectionModel,
+    results: &[CallContractResult],
+) -> NftCollectionModel {
+    collection.collection_uri = returned(results.first(), &[EventFieldType::ByteVec])
+        .and_then(|values| utf8_from_hex(values[0]));
+    collection.total_supply = returned(results.get(1), &[EventFieldType::U256])
+        .and_then(|values| BigDecimal::from_str(values[0]).ok());
+    collection
+}
+
+/// Fills the URI and collection index of an NFT from the results of `NFT_METHODS`, leaving the
+/// ones that couldn't be read empty.
+pub fn with_nft_metadata(mut nft: NftModel, results: &[CallContractResult]) -> NftModel {
+    nft.token_uri = returned(results.first(), &[EventFieldType::ByteVec])
+        .and_then(|values| utf8_from_hex(values[0]));
+    nft.nft_index = returned(results.get(1), &[EventFieldType::ByteVec, EventFieldType::U256])
+        .and_then(|values| BigDecimal::from_str(values[1]).ok());
+    nft
+}
+
+/// Returns the NFTs among a list of token ids.
+pub async fn get_known_nft_ids(db: Arc<DbPool>, token_ids: &[String]) -> Result<Vec<String>> {
+    use crate::schema::nfts;
+
+    if token_ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut conn = db.get().await?;
+    let ids = nfts::table
+        .filter(nfts::token_id.eq_any(token_ids))
+        .select(nfts::token_id)
+        .load(&mut conn)
+        .await?;
+    Ok(ids)
+}
+
+/// Insert NFT collections, NFTs and movements, then refreshes the owners of the NFTs moved, in
+/// a single DB transaction.
+pub async fn insert_nft_changes(
+    db: Arc<DbPool>,
+    mints: NftMints,
+    movements: Vec<NftMovementModel>,
+) -> Result<()> {
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            insert_into(crate::schema::nft_collections::table)
+                .values(&mints.collections)
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await?;
+            insert_into(crate::schema::nfts::table)
+                .values(&mints.nfts)
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await?;
+            insert_in_chunks(conn, &movements, |chunk| {
+                insert_into(crate::schema::nft_movements::table)
+                    .values(chunk)
+                    .on_conflict_do_nothing()
+            })
+            .await?;
+            let token_ids = movements.into_iter().map(|movement| movement.token_id).collect();
+            refresh_nft_owners(conn, token_ids).await
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Deletes the NFT collections, NFTs and movements of a list of blocks, then refreshes the
+/// owners of the NFTs involved, in a single DB transaction.
+pub async fn revert_nft_changes(db: Arc<DbPool>, block_hashes: &[BlockHash]) -> Result<()> {
+    use crate::schema::{nft_collections, nft_movements, nfts};
+
+    if block_hashes.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(
+                nft_collections::table
+                    .filter(nft_collections::creation_block_hash.eq_any(block_hashes)),
+            )
+            .execute(conn)
+            .await?;
+            diesel::delete(nfts::table.filter(nfts::minted_block_hash.eq_any(block_hashes)))
+                .execute(conn)
+                .await?;
+            let token_ids: Vec<String> = diesel::delete(
+                nft_movements::table.filter(nft_movements::block_hash.eq_any(block_hashes)),
+            )
+            .returning(nft_movements::token_id)
+            .get_results(conn)
+            .await?;
+            refresh_nft_owners(conn, token_ids).await
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Recomputes the owners of a list of NFTs from their latest movement.
+async fn refresh_nft_owners(
+    conn: &mut diesel_async::AsyncPgConnection,
+    mut token_ids: Vec<String>,
+) -> diesel::result::QueryResult<()> {
+    use crate::schema::nft_owners;
+
+    token_ids.sort_unstable();
+    token_ids.dedup();
+    if token_ids.is_empty() {
+        return Ok(());
+    }
+    diesel::delete(nft_owners::table.filter(nft_owners::token_id.eq_any(&token_ids)))
+        .execute(conn)
+        .await?;
+    sql_query(
+        "INSERT INTO nft_owners (token_id, owner_address, block_hash, tx_id, timestamp) \
+         SELECT DISTINCT ON (token_id) token_id, to_address, block_hash, tx_id, timestamp \
+         FROM nft_movements WHERE token_id = ANY($1) \
+         ORDER BY token_id, timestamp DESC, tx_index DESC",
+    )
+    .bind::<Array<Text>, _>(&token_ids)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Returns an NFT along with its current owner.
+pub async fn get_nft(
+    db: Arc<DbPool>,
+    token_id: &str,
+) -> Result<Option<(NftModel, Option<NftOwnerModel>)>> {
+    use crate::schema::{nft_owners, nfts};
+
+    let mut conn = db.get().await?;
+    let nft = nfts::table
+        .left_join(nft_owners::table.on(nft_owners::token_id.eq(nfts::token_id)))
+        .filter(nfts::token_id.eq(token_id))
+        .select((NftModel::as_select(), Option::<NftOwnerModel>::as_select()))
+        .first(&mut conn)
+        .await
+        .optional()?;
+    Ok(nft)
+}
+
+/// Returns the NFTs owned by an address.
+pub async fn get_nfts_of_owner(db: Arc<DbPool>, owner_address: &str) -> Result<Vec<NftModel>> {
+    use crate::schema::{nft_owners, nfts};
+
+    let mut conn = db.get().await?;
+    let nfts = nfts::table
+        .inner_join(nft_owners::table.on(nft_owners::token_id.eq(nfts::token_id)))
+        .filter(nft_owners::owner_address.eq(owner_address))
+        .order_by(nfts::minted_timestamp)
+        .select(NftModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(nfts)
+}
+
+/// Returns the NFTs of a collection, in minting order.
+pub async fn get_collection_nfts(
+    db: Arc<DbPool>,
+    collection_address: &str,
+) -> Result<Vec<NftModel>> {
+    use crate::schema::nfts;
+
+    let mut conn = db.get().await?;
+    let nfts = nfts::table
+        .filter(nfts::collection_address.eq(collection_address))
+        .order_by(nfts::minted_timestamp)
+        .select(NftModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(nfts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processors::contract_processor::CREATE_CONTRACT_EVENT_INDEX;
+    use crate::test_utils::block_and_events;
+    use crate::utils::address_from_contract_id;
+    use serde_json::json;
+
+    const COLLECTION_ID: &str = "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800";
+    const NFT_ID: &str = "25469eb0d0d0a55deea832924547b7b166c70a3554fe321e81886d3c18f19d64";
+    const NFT_ADDRESS: &str = "wCTeteGBeSEC54GpkS8jWBzYiYNTBUuTW3WzxGd9yExT";
+    const OWNER: &str = "1FQuaJLe6BAcHYoQdhW2TMENVpRgGS8rpMtLRsy9ZjbPq";
+
+    fn block(main_chain: bool) -> BlockAndEvents {
+        let collection = address_from_contract_id(COLLECTION_ID).unwrap();
+        let transactions = json!([{
+            "unsigned": {
+                "txId": "mint",
+                "version": 0,
+                "networkId": 0,
+                "gasAmount": 20000,
+                "gasPrice": "100000000000",
+                "inputs": [],
+                "fixedOutputs": []
+            },
+            "scriptExecutionOk": true,
+            "contractInputs": [],
+            "generatedOutputs": [
+                {
+                    "type": "AssetOutput",
+                    "hint": 1,
+                    "key": "minted",
+                    "attoAlphAmount": "1000000000000000",
+                    "address": OWNER,
+                    "tokens": [
+                        { "id": NFT_ID, "amount": "1" },
+                        { "id": "fungible", "amount": "10" }
+                    ],
+                    "lockTime": 0,
+                    "message": ""
+                }
+            ],
+            "inputSignatures": [],
+            "scriptSignatures": []
+        }]);
+        let events = json!([
+            {
+                "txId": "create_collection",
+                "contractAddress": "system",
+                "eventIndex": CREATE_CONTRACT_EVENT_INDEX,
+                "fields": [
+                    { "type": "Address", "value": collection },
+                    { "type": "ByteVec", "value": "" },
+                    { "type": "ByteVec", "value": "414c5048000201" }
+                ]
+            },
+            {
+                "txId": "mint",
+                "contractAddress": "system",
+                "eventIndex": CREATE_CONTRACT_EVENT_INDEX,
+                "fields": [
+                    { "type": "Address", "value": NFT_ADDRESS },
+                    { "type": "Address", "value": collection },
+                    { "type": "ByteVec", "value": "0003" }
+                ]
+            }
+        ]);
+        block_and_events("block", main_chain, 1735689600000, transactions, events)
+    }
+
+    #[test]
+    fn test_convert_to_nft_changes() {
+        let mints = convert_to_nft_mints(vec![block(true)]);
+        assert_eq!(mints.collections.len(), 1);
+        assert_eq!(mints.collections[0].contract_id, COLLECTION_ID);
+        assert_eq!(mints.collections[0].std_interface_id, "000201");
+        assert_eq!(mints.nfts.len(), 1);
+        assert_eq!(mints.nfts[0].token_id, NFT_ID);
+        assert_eq!(mints.nfts[0].collection_address, address_from_contract_id(COLLECTION_ID));
+
+        let nft_ids = HashSet::from([NFT_ID.to_string()]);
+        let movements = convert_to_nft_movements(&[block(true)], &nft_ids);
+        let owners: Vec<_> =
+            movements.iter().map(|m| (m.token_id.as_str(), m.to_address.as_str())).collect();
+        assert_eq!(owners, vec![(NFT_ID, OWNER)]);
+
+        // Blocks out of the main chain are ignored
+        assert_eq!(convert_to_nft_mints(vec![block(false)]), NftMints::default());
+        assert!(convert_to_nft_movements(&[block(false)], &nft_ids).is_empty());
+    }
+
+    #[test]
+    fn test_with_nft_metadata() {
+        let results: Vec<CallContractResult> = serde_json::from_value(json!([
+            {
+                "type": "CallContractSucceeded",
+                "returns": [{ "type": "ByteVec", "value": "697066733a2f2f31" }],
+                "gasUsed": 5000
+            },
+            {
+                "type": "CallContractSucceeded",
+                "returns": [
+                    { "type": "ByteVec", "value": COLLECTION_ID },
+                    { "type": "U256", "value": "7" }
+                ],
+                "gasUsed": 5000
+            }
+        ]))
+        .unwrap();
+        let nft = convert_to_nft_mints(vec![block(true)]).nfts.remove(0);
+
+        let nft = with_nft_metadata(nft, &results);
+        assert_eq!(nft.token_uri.as_deref(), Some("ipfs://1"));
+        assert_eq!(nft.nft_index, Some(BigDecimal::from(7)));
+
+        // A collection answering with unexpected types keeps its metadata empty
+        let collection = convert_to_nft_mints(vec![block(true)]).collections.remove(0);
+        let collection = with_collection_metadata(collection, &results);
+        assert_eq!(collection.collection_uri.as_deref(), Some("ipfs://1"));
+        assert_eq!(collection.total_supply, None);
+    }
+}
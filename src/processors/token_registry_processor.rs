@@ -0,0 +1,366 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use futures::{StreamExt, TryStreamExt};
+
+use crate::{
+    client::Client,
+    config::ProcessorConfig,
+    db::DbPool,
+    models::token::{TokenModel, TokenSighting},
+    processors::ProcessorTrait,
+    repository::{
+        clear_tokens_first_seen_in, get_tokens, insert_tokens_to_db, update_tokens_first_seen,
+        update_tokens_metadata,
+    },
+    types::{BlockAndEvents, BlockHash, CallContractResult, EventField, EventFieldType},
+    utils::{address_from_contract_id, timestamp_millis_to_naive_datetime},
+};
+
+/// Method indexes of the fungible token standard: `getSymbol`, `getName`, `getDecimals` and
+/// `getTotalSupply`.
+pub const FUNGIBLE_TOKEN_METHODS: [i32; 4] = [0, 1, 2, 3];
+
+/// Maximum number of tokens whose metadata is requested from the node at once.
+const METADATA_FETCH_CONCURRENCY: usize = 8;
+
+/// Metadata read from a contract implementing the fungible token standard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FungibleTokenMetadata {
+    pub symbol: String,
+    pub name: String,
+    pub decimals: i32,
+    pub total_supply: BigDecimal,
+}
+
+/// What the issuing contract of a token answered to the fungible token standard methods.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenMetadata {
+    Fungible(FungibleTokenMetadata),
+    /// The contract doesn't implement the standard.
+    NotFungible,
+    /// The node rejected the calls, a lagging node may not know the contract yet.
+    Unknown,
+}
+
+/// Registers the tokens appearing in the outputs of main chain blocks, along with the metadata
+/// read from their issuing contract when it implements the fungible token standard.
+///
+/// Metadata is read once, when a token is first seen, by calling the standard methods through
+/// `/contracts/multicall-contract`. Tokens whose contract doesn't answer them are registered
+/// as non-fungible, without metadata. When the node rejects the calls, the token is registered
+/// with an unknown `is_fungible` and its metadata is read again at its next sighting.
+pub struct TokenRegistryProcessor {
+    connection_pool: Arc<DbPool>,
+    client: Arc<Client>,
+    group_num: i64,
+}
+
+impl TokenRegistryProcessor {
+    pub fn new(connection_pool: Arc<DbPool>, client: Arc<Client>, group_num: i64) -> Self {
+        Self { connection_pool, client, group_num }
+    }
+
+    async fn insert(&self, blocks: Vec<BlockAndEvents>) -> Result<()> {
+        let sightings = convert_to_token_sightings(blocks);
+        if sightings.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<String> = sightings.iter().map(|s| s.token_id.clone()).collect();
+        let stored = get_tokens(self.connection_pool.clone(), &ids).await?;
+        let known: HashSet<&str> = stored.iter().map(|token| token.token_id.as_str()).collect();
+        let unknown_metadata: HashSet<&str> = stored
+            .iter()
+            .filter(|token| token.is_fungible.is_none())
+            .map(|token| token.token_id.as_str())
+            .collect();
+        let (known_sightings, new_sightings): (Vec<TokenSighting>, Vec<TokenSighting>) =
+            sightings.into_iter().partition(|s| known.contains(s.token_id.as_str()));
+        // Known tokens may have been seen first in a block that left the main chain, or in a
+        // later block when catching up out of order
+        update_tokens_first_seen(self.connection_pool.clone(), &known_sightings).await?;
+        let retried: Vec<TokenSighting> = known_sightings
+            .into_iter()
+            .filter(|s| unknown_metadata.contains(s.token_id.as_str()))
+            .collect();
+        if new_sightings.is_empty() && retried.is_empty() {
+            return Ok(());
+        }
+
+        let tokens = self.fetch_tokens(new_sightings).await?;
+        let retried = self.fetch_tokens(retried).await?;
+        tracing::info!(
+            processor_name = ?self.name(),
+            tokens = ?tokens.len(),
+            fungible = ?tokens.iter().filter(|token| token.is_fungible == Some(true)).count(),
+            unknown = ?tokens.iter().filter(|token| token.is_fungible.is_none()).count(),
+            retried = ?retried.len(),
+            "Found new tokens"
+        );
+        insert_tokens_to_db(self.connection_pool.clone(), tokens).await?;
+        update_tokens_metadata(self.connection_pool.clone(), &retried).await
+    }
+
+    /// Reads the metadata of the sighted tokens.
+    async fn fetch_tokens(&self, sightings: Vec<TokenSighting>) -> Result<Vec<TokenModel>> {
+        futures::stream::iter(sightings)
+            .map(|sighting| {
+                let client = self.client.clone();
+                let group_num = self.group_num;
+                async move {
+                    let metadata =
+                        fetch_fungible_token_metadata(&client, &sighting.token_id, group_num)
+                            .await?;
+                    anyhow::Ok(convert_to_token_model(sighting, metadata))
+                }
+            })
+            .buffered(METADATA_FETCH_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+}
+
+impl Debug for TokenRegistryProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "TokenRegistryProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+#[async_trait]
+impl ProcessorTrait for TokenRegistryProcessor {
+    fn name(&self) -> &'static str {
+        ProcessorConfig::TokenRegistryProcessor.name()
+    }
+
+    fn connection_pool(&self) -> &Arc<DbPool> {
+        &self.connection_pool
+    }
+
+    async fn process_blocks(
+        &self,
+        _from: i64,
+        _to: i64,
+        blocks: Vec<Vec<BlockAndEvents>>,
+    ) -> Result<()> {
+        self.insert(blocks.into_iter().flatten().collect()).await
+    }
+
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        // The tokens stay registered, they may be held by later blocks already processed
+        clear_tokens_first_seen_in(self.connection_pool.clone(), orphaned).await?;
+        self.insert(replacements).await
+    }
+}
+
+/// Returns the tokens held by the outputs of the main chain blocks, each with the earliest block
+/// it appears in.
+pub fn convert_to_token_sightings(
+    blocks: impl IntoIterator<Item = BlockAndEvents>,
+) -> Vec<TokenSighting> {
+    let mut blocks: Vec<BlockAndEvents> =
+        blocks.into_iter().filter(|be| be.block.main_chain).collect();
+    blocks.sort_by_key(|be| be.block.timestamp);
+
+    let mut seen = HashSet::new();
+    let mut sightings = Vec::new();
+    for be in blocks.iter() {
+        for tx in be.block.transactions.iter() {
+            let fixed = tx.unsigned.fixed_outputs.iter().flat_map(|output| output.tokens.iter());
+            let generated = tx.generated_outputs.iter().flat_map(|output| output.tokens().iter());
+            for token in fixed.chain(generated) {
+                if seen.insert(token.id.clone()) {
+                    sightings.push(TokenSighting {
+                        token_id: token.id.clone(),
+                        block_hash: be.block.hash.clone(),
+                        timestamp: timestamp_millis_to_naive_datetime(be.block.timestamp),
+                    });
+                }
+            }
+        }
+    }
+    sightings
+}
+
+/// Calls the fungible token methods of the contract issuing a token. Returns an error only when
+/// the node can't be reached.
+pub async fn fetch_fungible_token_metadata(
+    client: &Client,
+    token_id: &str,
+    group_num: i64,
+) -> Result<TokenMetadata> {
+    let results =
+        client.call_contract_methods(token_id, &FUNGIBLE_TOKEN_METHODS, group_num).await?;
+    if results.is_empty() {
+        return Ok(TokenMetadata::Unknown);
+    }
+    Ok(parse_fungible_token_metadata(&results)
+        .map_or(TokenMetadata::NotFungible, TokenMetadata::Fungible))
+}
+
+/// Reads the fungible token metadata out of the results of the standard method calls, in the
+/// order of `FUNGIBLE_TOKEN_METHODS`.
+pub fn parse_fungible_token_metadata(
+    results: &[CallContractResult],
+) -> Option<FungibleTokenMetadata> {
+    let returned = |index: usize, field_type: EventFieldType| match results
+        .get(index)
+        .and_then(CallContractResult::returns)?
+    {
+        [EventField { field_type: t, value }] if *t == field_type => value.as_str(),
+        _ => None,
+    };
+
+    Some(FungibleTokenMetadata {
+        symbol: utf8_from_hex(returned(0, EventFieldType::ByteVec)?)?,
+        name: utf8_from_hex(returned(1, EventFieldType::ByteVec)?)?,
+        decimals: returned(2, EventFieldType::U256)?.parse().ok()?,
+        total_supply: BigDecimal::from_str(returned(3, EventFieldType::U256)?).ok()?,
+    })
+}
+
+/// Decodes a hex `ByteVec` holding UTF-8 text, as returned by the standard token methods.
+pub fn utf8_from_hex(value: &str) -> Option<String> {
+    String::from_utf8(hex::decode(value).ok()?).ok()
+}
+
+fn convert_to_token_model(sighting: TokenSighting, metadata: TokenMetadata) -> TokenModel {
+    let contract_address = address_from_contract_id(&sighting.token_id);
+    let is_fungible = match metadata {
+        TokenMetadata::Fungible(_) => Some(true),
+        TokenMetadata::NotFungible => Some(false),
+        TokenMetadata::Unknown => None,
+    };
+    let (name, symbol, decimals, total_supply) = match metadata {
+        TokenMetadata::Fungible(m) => {
+            (Some(m.name), Some(m.symbol), Some(m.decimals), Some(m.total_supply))
+        }
+        _ => (None, None, None, None),
+    };
+    TokenModel {
+        token_id: sighting.token_id,
+        contract_address,
+        name,
+        symbol,
+        decimals,
+        total_supply,
+        is_fungible,
+        first_seen_block_hash: Some(sighting.block_hash),
+        first_seen_timestamp: Some(sighting.timestamp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{FailoverPolicy, RetryPolicy};
+    use crate::test_utils::{healthy_node, serve};
+    use crate::types::DEFAULT_GROUP_NUM;
+    use serde_json::json;
+
+    fn results(value: serde_json::Value) -> Vec<CallContractResult> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn succeeded(field_type: &str, value: &str) -> serde_json::Value {
+        json!({
+            "type": "CallContractSucceeded",
+            "returns": [{ "type": field_type, "value": value }],
+            "gasUsed": 5000,
+            "contracts": [],
+            "txInputs": [],
+            "txOutputs": [],
+            "events": [],
+            "debugMessages": []
+        })
+    }
+
+    #[test]
+    fn test_parse_fungible_token_metadata() {
+        let metadata = parse_fungible_token_metadata(&results(json!([
+            succeeded("ByteVec", "55534454"),
+            succeeded("ByteVec", "54657468657220555344"),
+            succeeded("U256", "6"),
+            succeeded("U256", "1000000000000000")
+        ])));
+        assert_eq!(
+            metadata,
+            Some(FungibleTokenMetadata {
+                symbol: "USDT".into(),
+                name: "Tether USD".into(),
+                decimals: 6,
+                total_supply: BigDecimal::from(1_000_000_000_000_000i64),
+            })
+        );
+
+        // Contract not implementing `getTotalSupply`
+        assert_eq!(
+            parse_fungible_token_metadata(&results(json!([
+                succeeded("ByteVec", "55534454"),
+                succeeded("ByteVec", "54657468657220555344"),
+                succeeded("U256", "6"),
+                { "type": "CallContractFailed", "error": "InvalidMethodIndex" }
+            ]))),
+            None
+        );
+        // Methods returning other types
+        assert_eq!(
+            parse_fungible_token_metadata(&results(json!([
+                succeeded("ByteVec", "55534454"),
+                succeeded("ByteVec", "54657468657220555344"),
+                succeeded("Bool", "true"),
+                succeeded("U256", "1000000000000000")
+            ]))),
+            None
+        );
+        // Symbol not valid UTF-8
+        assert_eq!(
+            parse_fungible_token_metadata(&results(json!([
+                succeeded("ByteVec", "ff"),
+                succeeded("ByteVec", "54657468657220555344"),
+                succeeded("U256", "6"),
+                succeeded("U256", "1000000000000000")
+            ]))),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_fungible_token_metadata_unknown() {
+        // A lagging node doesn't know the contract yet, the metadata is to be read again
+        let rejecting = serve(|path| {
+            healthy_node(path).unwrap_or((400, r#"{"detail": "Contract not found"}"#))
+        })
+        .await;
+        let client = Client::new_with_endpoints(
+            vec![rejecting],
+            RetryPolicy::no_retry(),
+            FailoverPolicy::default(),
+        )
+        .unwrap();
+        let token_id = "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800";
+        let metadata = fetch_fungible_token_metadata(&client, token_id, DEFAULT_GROUP_NUM).await;
+        assert_eq!(metadata.unwrap(), TokenMetadata::Unknown);
+
+        let sighting = TokenSighting {
+            token_id: token_id.into(),
+            block_hash: "block".into(),
+            timestamp: timestamp_millis_to_naive_datetime(1735689600000),
+        };
+        assert_eq!(convert_to_token_model(sighting, TokenMetadata::Unknown).is_fungible, None);
+    }
+}
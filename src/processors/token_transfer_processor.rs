@@ -0,0 +1,537 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use chrono::NaiveDateTime;
+use diesel::insert_into;
+use diesel::prelude::*;
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use serde::Serialize;
+
+use crate::{
+    config::ProcessorConfig,
+    db::DbPool,
+    processors::ProcessorTrait,
+    repository::insert_in_chunks,
+    types::{BlockAndEvents, BlockHash, Output, Token, Transaction, ALPH_TOKEN_ID},
+    utils::{address_from_unlock_script, parse_amount, timestamp_millis_to_naive_datetime},
+};
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::transfer_outputs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TransferOutputModel {
+    pub key: String,
+    pub address: String,
+    pub atto_alph_amount: BigDecimal,
+    pub tokens: serde_json::Value,
+    pub block_hash: String,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::transfers)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TransferModel {
+    pub tx_id: String,
+    pub block_hash: String,
+    pub timestamp: NaiveDateTime,
+    pub from_addresses: Vec<String>,
+    pub to_address: String,
+    pub token_id: String,
+    pub amount: BigDecimal,
+}
+
+/// Records the ALPH and token movements of every main chain transaction.
+///
+/// A transfer is the net amount of ALPH or of a token an address received in a transaction,
+/// sent by the addresses whose balance of it decreased. Change outputs therefore don't show up
+/// as transfers, and contract inputs and generated outputs are accounted for like any other.
+/// The owners of the inputs are resolved from the outputs seen before. Transactions spending
+/// outputs created before the first processed block can't be netted: every output they create
+/// is recorded as a transfer of its gross amount, change included, from the addresses of their
+/// inputs.
+pub struct TokenTransferProcessor {
+    connection_pool: Arc<DbPool>,
+}
+
+impl TokenTransferProcessor {
+    pub fn new(connection_pool: Arc<DbPool>) -> Self {
+        Self { connection_pool }
+    }
+
+    async fn insert(&self, blocks: Vec<BlockAndEvents>) -> Result<()> {
+        let blocks: Vec<_> = blocks.into_iter().filter(|be| be.block.main_chain).collect();
+        let outputs = convert_to_transfer_output_models(&blocks)?;
+        if outputs.is_empty() {
+            return Ok(());
+        }
+
+        // Inputs spend outputs of this batch or of a previous one
+        let mut known: HashMap<String, TransferOutputModel> =
+            outputs.iter().map(|output| (output.key.clone(), output.clone())).collect();
+        let missing: Vec<String> =
+            input_keys(&blocks).filter(|key| !known.contains_key(*key)).map(String::from).collect();
+        for output in get_transfer_outputs(self.connection_pool.clone(), &missing).await? {
+            known.insert(output.key.clone(), output);
+        }
+
+        let unresolved = blocks
+            .iter()
+            .flat_map(|be| be.block.transactions.iter())
+            .filter(|tx| !tx_input_keys(tx).all(|key| known.contains_key(key)))
+            .count();
+        if unresolved > 0 {
+            tracing::warn!(
+                processor_name = ?self.name(),
+                transactions = unresolved,
+                "Transactions spend outputs that aren't indexed, recording gross transfers"
+            );
+        }
+
+        let transfers = convert_to_transfer_models(&blocks, &known)?;
+        tracing::info!(
+            processor_name = ?self.name(),
+            outputs = ?outputs.len(),
+            transfers = ?transfers.len(),
+            "Found models to insert"
+        );
+        insert_transfers(self.connection_pool.clone(), outputs, transfers).await
+    }
+}
+
+impl Debug for TokenTransferProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "TokenTransferProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+#[async_trait]
+impl ProcessorTrait for TokenTransferProcessor {
+    fn name(&self) -> &'static str {
+        ProcessorConfig::TokenTransferProcessor.name()
+    }
+
+    fn connection_pool(&self) -> &Arc<DbPool> {
+        &self.connection_pool
+    }
+
+    async fn process_blocks(
+        &self,
+        _from: i64,
+        _to: i64,
+        blocks: Vec<Vec<BlockAndEvents>>,
+    ) -> Result<()> {
+        self.insert(blocks.into_iter().flatten().collect()).await
+    }
+
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        delete_transfers_of_blocks(self.connection_pool.clone(), orphaned).await?;
+        self.insert(replacements).await
+    }
+}
+
+/// Returns the outputs created by the transactions of the blocks.
+pub fn convert_to_transfer_output_models(
+    blocks: &[BlockAndEvents],
+) -> Result<Vec<TransferOutputModel>> {
+    let mut models = Vec::new();
+    for be in blocks {
+        for tx in be.block.transactions.iter() {
+            for output in outputs(tx) {
+                models.push(TransferOutputModel {
+                    key: output.key().to_string(),
+                    address: output.address().to_string(),
+                    atto_alph_amount: parse_amount(output.atto_alph_amount())?,
+                    tokens: serde_json::to_value(output.tokens())?,
+                    block_hash: be.block.hash.clone(),
+                });
+            }
+        }
+    }
+    Ok(models)
+}
+
+/// Derives the transfers of the transactions of the blocks, `known` holding the outputs spent
+/// by their inputs. The transfers of transactions spending unknown outputs have gross amounts,
+/// see [`convert_to_gross_transfer_models`].
+pub fn convert_to_transfer_models(
+    blocks: &[BlockAndEvents],
+    known: &HashMap<String, TransferOutputModel>,
+) -> Result<Vec<TransferModel>> {
+    let mut models = Vec::new();
+    for be in blocks {
+        let timestamp = timestamp_millis_to_naive_datetime(be.block.timestamp);
+        for tx in be.block.transactions.iter() {
+            // Net change of the holdings of every address, by token
+            let mut deltas: BTreeMap<(String, String), BigDecimal> = BTreeMap::new();
+            let inputs: Option<Vec<_>> = tx_input_keys(tx).map(|key| known.get(key)).collect();
+            let Some(inputs) = inputs else {
+                // The amounts sent can't be told apart from the change
+                tracing::debug!(tx_id = tx.unsigned.tx_id, "Unknown inputs, gross transfers");
+                models.extend(convert_to_gross_transfer_models(
+                    tx,
+                    known,
+                    &be.block.hash,
+                    timestamp,
+                )?);
+                continue;
+            };
+            for input in inputs {
+                let tokens: Vec<Token> = serde_json::from_value(input.tokens.clone())?;
+                for (token_id, amount) in holdings(&input.atto_alph_amount, &tokens)? {
+                    *deltas.entry((token_id, input.address.clone())).or_default() -= amount;
+                }
+            }
+            for output in outputs(tx) {
+                let atto_alph_amount = parse_amount(output.atto_alph_amount())?;
+                for (token_id, amount) in holdings(&atto_alph_amount, output.tokens())? {
+                    *deltas.entry((token_id, output.address().to_string())).or_default() += amount;
+                }
+            }
+
+            let mut senders: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+            for ((token_id, address), delta) in deltas.iter() {
+                if delta < &BigDecimal::zero() {
+                    senders.entry(token_id).or_default().insert(address);
+                }
+            }
+            for ((token_id, address), delta) in deltas.iter() {
+                if delta > &BigDecimal::zero() {
+                    let from_addresses = senders.get(token_id.as_str()).into_iter().flatten();
+                    models.push(TransferModel {
+                        tx_id: tx.unsigned.tx_id.clone(),
+                        block_hash: be.block.hash.clone(),
+                        timestamp,
+                        from_addresses: from_addresses.map(|from| from.to_string()).collect(),
+                        to_address: address.clone(),
+                        token_id: token_id.clone(),
+                        amount: delta.clone(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(models)
+}
+
+/// Returns the outputs of a transaction as transfers from the addresses of its inputs, for a
+/// transaction spending outputs missing from `known`. The amounts are gross: the change is
+/// included, and nothing is netted against what the receiving address spent. The addresses of
+/// the unknown inputs are derived from their unlock scripts, which is only possible for P2PKH
+/// inputs.
+fn convert_to_gross_transfer_models(
+    tx: &Transaction,
+    known: &HashMap<String, TransferOutputModel>,
+    block_hash: &str,
+    timestamp: NaiveDateTime,
+) -> Result<Vec<TransferModel>> {
+    let mut from_addresses: BTreeSet<String> = tx
+        .unsigned
+        .inputs
+        .iter()
+        .filter_map(|input| address_from_unlock_script(&input.unlock_script))
+        .collect();
+    from_addresses
+        .extend(tx_input_keys(tx).filter_map(|key| Some(known.get(key)?.address.clone())));
+
+    let mut received: BTreeMap<(String, String), BigDecimal> = BTreeMap::new();
+    for output in outputs(tx) {
+        let atto_alph_amount = parse_amount(output.atto_alph_amount())?;
+        for (token_id, amount) in holdings(&atto_alph_amount, output.tokens())? {
+            *received.entry((token_id, output.address().to_string())).or_default() += amount;
+        }
+    }
+    Ok(received
+        .into_iter()
+        .filter(|(_, amount)| amount > &BigDecimal::zero())
+        .map(|((token_id, to_address), amount)| TransferModel {
+            tx_id: tx.unsigned.tx_id.clone(),
+            block_hash: block_hash.to_string(),
+            timestamp,
+            from_addresses: from_addresses.iter().cloned().collect(),
+            to_address,
+            token_id,
+            amount,
+        })
+        .collect())
+}
+
+/// Returns the fixed and generated outputs of a transaction.
+fn outputs(tx: &Transaction) -> impl Iterator<Item = Output> + '_ {
+    let fixed_outputs = tx.unsigned.fixed_outputs.iter().cloned();
+    fixed_outputs
+        .map(|output| Output::AssetOutput(output.into()))
+        .chain(tx.generated_outputs.clone())
+}
+
+/// Returns the keys of the asset and contract outputs spent by a transaction.
+fn tx_input_keys(tx: &Transaction) -> impl Iterator<Item = &str> {
+    let inputs = tx.unsigned.inputs.iter().map(|input| input.output_ref.key.as_str());
+    inputs.chain(tx.contract_inputs.iter().map(|input| input.key.as_str()))
+}
+
+fn input_keys(blocks: &[BlockAndEvents]) -> impl Iterator<Item = &str> {
+    blocks.iter().flat_map(|be| be.block.transactions.iter()).flat_map(tx_input_keys)
+}
+
+/// Returns the amounts of ALPH and of each token held by an output, ALPH being listed under
+/// [`ALPH_TOKEN_ID`].
+fn holdings(atto_alph_amount: &BigDecimal, tokens: &[Token]) -> Result<Vec<(String, BigDecimal)>> {
+    let mut holdings = vec![(ALPH_TOKEN_ID.to_string(), atto_alph_amount.clone())];
+    for token in tokens {
+        holdings.push((token.id.clone(), parse_amount(&token.amount)?));
+    }
+    Ok(holdings)
+}
+
+/// Returns the stored outputs among a list of keys.
+pub async fn get_transfer_outputs(
+    db: Arc<DbPool>,
+    keys: &[String],
+) -> Result<Vec<TransferOutputModel>> {
+    use crate::schema::transfer_outputs;
+
+    if keys.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut conn = db.get().await?;
+    let outputs = transfer_outputs::table
+        .filter(transfer_outputs::key.eq_any(keys))
+        .select(TransferOutputModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(outputs)
+}
+
+/// Insert outputs and transfers into the database in a single DB transaction, skipping rows
+/// that are already stored.
+pub async fn insert_transfers(
+    db: Arc<DbPool>,
+    outputs: Vec<TransferOutputModel>,
+    transfers: Vec<TransferModel>,
+) -> Result<()> {
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            insert_in_chunks(conn, &outputs, |chunk| {
+                insert_into(crate::schema::transfer_outputs::table)
+                    .values(chunk)
+                    .on_conflict_do_nothing()
+            })
+            .await?;
+            insert_in_chunks(conn, &transfers, |chunk| {
+                insert_into(crate::schema::transfers::table).values(chunk).on_conflict_do_nothing()
+            })
+            .await?;
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Delete the outputs and transfers of a list of blocks.
+pub async fn delete_transfers_of_blocks(db: Arc<DbPool>, block_hashes: &[BlockHash]) -> Result<()> {
+    use crate::schema::{transfer_outputs, transfers};
+
+    if block_hashes.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            diesel::delete(transfers::table.filter(transfers::block_hash.eq_any(block_hashes)))
+                .execute(conn)
+                .await?;
+            diesel::delete(
+                transfer_outputs::table.filter(transfer_outputs::block_hash.eq_any(block_hashes)),
+            )
+            .execute(conn)
+            .await?;
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Returns the transfers sent or received by an address, most recent first.
+pub async fn get_transfers_of_address(
+    db: Arc<DbPool>,
+    address: &str,
+    limit: i64,
+) -> Result<Vec<TransferModel>> {
+    use crate::schema::transfers;
+
+    let mut conn = db.get().await?;
+    let models = transfers::table
+        .filter(
+            transfers::to_address
+                .eq(address)
+                .or(transfers::from_addresses.contains(vec![address.to_string()])),
+        )
+        .order_by(transfers::timestamp.desc())
+        .limit(limit)
+        .select(TransferModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(models)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::block_and_events;
+    use serde_json::json;
+
+    const TOKEN_ID: &str = "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800";
+
+    fn block(transactions: serde_json::Value) -> BlockAndEvents {
+        block_and_events("block", true, 1735689600000, transactions, json!([]))
+    }
+
+    fn known_output(
+        key: &str,
+        address: &str,
+        atto_alph_amount: u64,
+        tokens: serde_json::Value,
+    ) -> TransferOutputModel {
+        TransferOutputModel {
+            key: key.into(),
+            address: address.into(),
+            atto_alph_amount: BigDecimal::from(atto_alph_amount),
+            tokens,
+            block_hash: "previous".into(),
+        }
+    }
+
+    // Alice swaps 10 ALPH for 50 tokens with a pool contract, paying 1 ALPH of gas
+    fn swap_tx(input_key: &str) -> serde_json::Value {
+        json!([{
+            "unsigned": {
+                "txId": "swap",
+                "version": 0,
+                "networkId": 0,
+                "gasAmount": 20000,
+                "gasPrice": "100000000000",
+                "inputs": [
+                    { "outputRef": { "hint": 1, "key": input_key }, "unlockScript": "00" }
+                ],
+                "fixedOutputs": [{
+                    "hint": 2,
+                    "key": "alice_change",
+                    "attoAlphAmount": "89",
+                    "address": "alice",
+                    "tokens": [],
+                    "lockTime": 0,
+                    "message": ""
+                }]
+            },
+            "scriptExecutionOk": true,
+            "contractInputs": [{ "hint": 3, "key": "pool_before" }],
+            "generatedOutputs": [
+                {
+                    "type": "ContractOutput",
+                    "hint": 3,
+                    "key": "pool_after",
+                    "attoAlphAmount": "1010",
+                    "address": "pool",
+                    "tokens": [{ "id": TOKEN_ID, "amount": "450" }]
+                },
+                {
+                    "type": "AssetOutput",
+                    "hint": 2,
+                    "key": "alice_tokens",
+                    "attoAlphAmount": "0",
+                    "address": "alice",
+                    "tokens": [{ "id": TOKEN_ID, "amount": "50" }],
+                    "lockTime": 0,
+                    "message": ""
+                }
+            ],
+            "inputSignatures": [],
+            "scriptSignatures": []
+        }])
+    }
+
+    #[test]
+    fn test_convert_to_transfer_models() {
+        let known = HashMap::from([
+            ("alice_input".to_string(), known_output("alice_input", "alice", 100, json!([]))),
+            (
+                "pool_before".to_string(),
+                known_output(
+                    "pool_before",
+                    "pool",
+                    1000,
+                    json!([{ "id": TOKEN_ID, "amount": "500" }]),
+                ),
+            ),
+        ]);
+        let blocks = vec![block(swap_tx("alice_input"))];
+
+        let outputs = convert_to_transfer_output_models(&blocks).unwrap();
+        let keys: Vec<_> = outputs.iter().map(|output| output.key.as_str()).collect();
+        assert_eq!(keys, vec!["alice_change", "pool_after", "alice_tokens"]);
+
+        let transfers = convert_to_transfer_models(&blocks, &known).unwrap();
+        let transfers: Vec<_> = transfers
+            .iter()
+            .map(|t| {
+                (
+                    t.from_addresses.clone(),
+                    t.to_address.as_str(),
+                    t.token_id.as_str(),
+                    t.amount.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            transfers,
+            vec![
+                (vec!["alice".to_string()], "pool", ALPH_TOKEN_ID, BigDecimal::from(10)),
+                (vec!["pool".to_string()], "alice", TOKEN_ID, BigDecimal::from(50)),
+            ]
+        );
+
+        // The change can't be told apart when an input is unknown, every output is a transfer
+        // from the addresses of the inputs
+        let mut tx = swap_tx("unknown");
+        tx[0]["unsigned"]["inputs"][0]["unlockScript"] =
+            json!("000381818e63bd9e35a5489b52a430accefc608fd60aa2c7c0d1b393b5239aedf6b2");
+        let transfers = convert_to_transfer_models(&[block(tx)], &known).unwrap();
+        for transfer in transfers.iter() {
+            assert_eq!(
+                transfer.from_addresses,
+                vec!["1FQuaJLe6BAcHYoQdhW2TMENVpRgGS8rpMtLRsy9ZjbPq", "pool"]
+            );
+        }
+        let transfers: Vec<_> = transfers
+            .iter()
+            .map(|t| (t.to_address.as_str(), t.token_id.as_str(), t.amount.clone()))
+            .collect();
+        assert_eq!(
+            transfers,
+            vec![
+                ("alice", ALPH_TOKEN_ID, BigDecimal::from(89)),
+                ("pool", ALPH_TOKEN_ID, BigDecimal::from(1010)),
+                ("alice", TOKEN_ID, BigDecimal::from(50)),
+                ("pool", TOKEN_ID, BigDecimal::from(450)),
+            ]
+        );
+    }
+}
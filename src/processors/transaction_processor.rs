@@ -0,0 +1,91 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::{
+    config::ProcessorConfig,
+    db::DbPool,
+    models::{convert_block_entry_to_block_model, convert_block_entry_to_tx_models},
+    repository::{insert_blocks_and_txs, update_txs_main_chain_status},
+    types::{BlockAndEvents, BlockHash},
+};
+
+use super::ProcessorTrait;
+
+/// Stores blocks along with their transactions.
+pub struct TransactionProcessor {
+    connection_pool: Arc<DbPool>,
+}
+
+impl TransactionProcessor {
+    pub fn new(connection_pool: Arc<DbPool>) -> Self {
+        Self { connection_pool }
+    }
+
+    /// Inserts the blocks and their transactions in a single DB transaction.
+    async fn insert(&self, blocks: Vec<BlockAndEvents>) -> Result<()> {
+        let mut block_models = Vec::with_capacity(blocks.len());
+        let mut tx_models = Vec::new();
+        for be in blocks {
+            tx_models.extend(convert_block_entry_to_tx_models(&be.block)?);
+            block_models.push(convert_block_entry_to_block_model(be.block));
+        }
+        if block_models.is_empty() {
+            return Ok(());
+        }
+        tracing::info!(
+            processor_name = ?self.name(),
+            blocks = ?block_models.len(),
+            txs = ?tx_models.len(),
+            "Found models to insert"
+        );
+        insert_blocks_and_txs(self.connection_pool.clone(), block_models, tx_models).await
+    }
+}
+
+impl Debug for TransactionProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "TransactionProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+#[async_trait]
+impl ProcessorTrait for TransactionProcessor {
+    fn name(&self) -> &'static str {
+        ProcessorConfig::TransactionProcessor.name()
+    }
+
+    fn connection_pool(&self) -> &Arc<DbPool> {
+        &self.connection_pool
+    }
+
+    async fn process_blocks(
+        &self,
+        _from: i64,
+        _to: i64,
+        blocks: Vec<Vec<BlockAndEvents>>,
+    ) -> Result<()> {
+        self.insert(blocks.into_iter().flatten().collect()).await
+    }
+
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        // Keep the transactions of orphaned blocks, marked as such
+        update_txs_main_chain_status(self.connection_pool.clone(), orphaned, false).await?;
+
+        // Transactions of replacements may have been stored while they were not in the main chain
+        let hashes: Vec<BlockHash> = replacements.iter().map(|be| be.block.hash.clone()).collect();
+        self.insert(replacements).await?;
+        update_txs_main_chain_status(self.connection_pool.clone(), &hashes, true).await
+    }
+}
@@ -0,0 +1,412 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, Text};
+use diesel::{insert_into, sql_query};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use serde::Serialize;
+
+use crate::{
+    config::ProcessorConfig,
+    db::DbPool,
+    processors::ProcessorTrait,
+    repository::insert_in_chunks,
+    types::{BlockAndEvents, BlockHash, Output, Token, ALPH_TOKEN_ID},
+    utils::{parse_amount, timestamp_millis_to_naive_datetime},
+};
+
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::utxos)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UtxoModel {
+    pub key: String,
+    pub tx_id: String,
+    pub output_index: i32,
+    pub block_hash: String,
+    pub block_timestamp: NaiveDateTime,
+    pub address: String,
+    pub atto_alph_amount: BigDecimal,
+    pub tokens: serde_json::Value,
+    pub lock_time: Option<i64>,
+    pub is_contract: bool,
+    pub spent_tx_id: Option<String>,
+    pub spent_block_hash: Option<String>,
+}
+
+#[derive(Queryable, Selectable, Debug, Clone, PartialEq, Serialize)]
+#[diesel(table_name = crate::schema::address_balances)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AddressBalanceModel {
+    pub address: String,
+    pub token_id: String,
+    pub amount: BigDecimal,
+}
+
+/// An output consumed by a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpentOutput {
+    pub key: String,
+    pub tx_id: String,
+    pub block_hash: String,
+}
+
+/// Maintains the set of unspent outputs and the balance of every address holding one.
+///
+/// Only main chain blocks are applied, the outputs created and spent by blocks leaving the main
+/// chain are reverted. Outputs created before the first processed block are unknown, so the
+/// balances are only complete when syncing from genesis.
+pub struct UtxoProcessor {
+    connection_pool: Arc<DbPool>,
+}
+
+impl UtxoProcessor {
+    pub fn new(connection_pool: Arc<DbPool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+impl Debug for UtxoProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "UtxoProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+#[async_trait]
+impl ProcessorTrait for UtxoProcessor {
+    fn name(&self) -> &'static str {
+        ProcessorConfig::UtxoProcessor.name()
+    }
+
+    fn connection_pool(&self) -> &Arc<DbPool> {
+        &self.connection_pool
+    }
+
+    async fn process_blocks(
+        &self,
+        _from: i64,
+        _to: i64,
+        blocks: Vec<Vec<BlockAndEvents>>,
+    ) -> Result<()> {
+        let (created, spent) = convert_to_utxo_changes(blocks.into_iter().flatten())?;
+        if created.is_empty() && spent.is_empty() {
+            return Ok(());
+        }
+        tracing::info!(
+            processor_name = ?self.name(),
+            created = ?created.len(),
+            spent = ?spent.len(),
+            "Found outputs to apply"
+        );
+        apply_utxo_changes(self.connection_pool.clone(), created, spent).await
+    }
+
+    async fn handle_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        replacements: Vec<BlockAndEvents>,
+    ) -> Result<()> {
+        revert_utxo_changes(self.connection_pool.clone(), orphaned).await?;
+        let (created, spent) = convert_to_utxo_changes(replacements)?;
+        apply_utxo_changes(self.connection_pool.clone(), created, spent).await
+    }
+}
+
+/// Returns the outputs created and spent by the transactions of the main chain blocks.
+pub fn convert_to_utxo_changes(
+    blocks: impl IntoIterator<Item = BlockAndEvents>,
+) -> Result<(Vec<UtxoModel>, Vec<SpentOutput>)> {
+    let mut created = Vec::new();
+    let mut spent = Vec::new();
+    for be in blocks.into_iter().filter(|be| be.block.main_chain) {
+        let block_timestamp = timestamp_millis_to_naive_datetime(be.block.timestamp);
+        for tx in be.block.transactions {
+            let tx_id = tx.unsigned.tx_id;
+            let inputs = tx.unsigned.inputs.into_iter().map(|input| input.output_ref.key);
+            let contract_inputs = tx.contract_inputs.into_iter().map(|input| input.key);
+            spent.extend(inputs.chain(contract_inputs).map(|key| SpentOutput {
+                key,
+                tx_id: tx_id.clone(),
+                block_hash: be.block.hash.clone(),
+            }));
+
+            let fixed_outputs = tx.unsigned.fixed_outputs.into_iter();
+            let outputs = fixed_outputs
+                .map(|output| Output::AssetOutput(output.into()))
+                .chain(tx.generated_outputs);
+            for (index, output) in outputs.enumerate() {
+                let lock_time = match &output {
+                    Output::AssetOutput(output) => Some(output.lock_time),
+                    Output::ContractOutput(_) => None,
+                };
+                created.push(UtxoModel {
+                    key: output.key().to_string(),
+                    tx_id: tx_id.clone(),
+                    output_index: index as i32,
+                    block_hash: be.block.hash.clone(),
+                    block_timestamp,
+                    address: output.address().to_string(),
+                    atto_alph_amount: parse_amount(output.atto_alph_amount())?,
+                    tokens: serde_json::to_value(validate_tokens(output.tokens())?)?,
+                    lock_time,
+                    is_contract: matches!(output, Output::ContractOutput(_)),
+                    spent_tx_id: None,
+                    spent_block_hash: None,
+                });
+            }
+        }
+    }
+    Ok((created, spent))
+}
+
+/// Checks the token amounts are numbers, as they are summed up in the database.
+fn validate_tokens(tokens: &[Token]) -> Result<&[Token]> {
+    for token in tokens {
+        parse_amount(&token.amount)?;
+    }
+    Ok(tokens)
+}
+
+/// Inserts the created outputs and marks the spent ones, then refreshes the balances of the
+/// addresses involved, in a single DB transaction.
+pub async fn apply_utxo_changes(
+    db: Arc<DbPool>,
+    created: Vec<UtxoModel>,
+    spent: Vec<SpentOutput>,
+) -> Result<()> {
+    if created.is_empty() && spent.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            use crate::schema::utxos;
+
+            let mut addresses: Vec<String> =
+                created.iter().map(|utxo| utxo.address.clone()).collect();
+            insert_in_chunks(conn, &created, |chunk| {
+                insert_into(utxos::table).values(chunk).on_conflict_do_nothing()
+            })
+            .await?;
+
+            let keys: Vec<&str> = spent.iter().map(|output| output.key.as_str()).collect();
+            let tx_ids: Vec<&str> = spent.iter().map(|output| output.tx_id.as_str()).collect();
+            let block_hashes: Vec<&str> =
+                spent.iter().map(|output| output.block_hash.as_str()).collect();
+            addresses.extend(
+                sql_query(
+                    "UPDATE utxos SET spent_tx_id = spent.tx_id, spent_block_hash = spent.block_hash \
+                     FROM unnest($1, $2, $3) AS spent(key, tx_id, block_hash) \
+                     WHERE utxos.key = spent.key \
+                     RETURNING utxos.address",
+                )
+                .bind::<Array<Text>, _>(keys)
+                .bind::<Array<Text>, _>(tx_ids)
+                .bind::<Array<Text>, _>(block_hashes)
+                .load::<AffectedAddress>(conn)
+                .await?
+                .into_iter()
+                .map(|affected| affected.address),
+            );
+
+            refresh_address_balances(conn, addresses).await
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Deletes the outputs created by a list of blocks and unspends the outputs they spent, then
+/// refreshes the balances of the addresses involved, in a single DB transaction.
+pub async fn revert_utxo_changes(db: Arc<DbPool>, block_hashes: &[BlockHash]) -> Result<()> {
+    if block_hashes.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            use crate::schema::utxos;
+
+            let mut addresses: Vec<String> =
+                diesel::delete(utxos::table.filter(utxos::block_hash.eq_any(block_hashes)))
+                    .returning(utxos::address)
+                    .get_results(conn)
+                    .await?;
+            addresses.extend(
+                diesel::update(utxos::table.filter(utxos::spent_block_hash.eq_any(block_hashes)))
+                    .set((
+                        utxos::spent_tx_id.eq(None::<String>),
+                        utxos::spent_block_hash.eq(None::<String>),
+                    ))
+                    .returning(utxos::address)
+                    .get_results::<String>(conn)
+                    .await?,
+            );
+
+            refresh_address_balances(conn, addresses).await
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Returns the balances of an address, ALPH being stored under [`ALPH_TOKEN_ID`].
+pub async fn get_address_balances(
+    db: Arc<DbPool>,
+    address: &str,
+) -> Result<Vec<AddressBalanceModel>> {
+    use crate::schema::address_balances;
+
+    let mut conn = db.get().await?;
+    let balances = address_balances::table
+        .filter(address_balances::address.eq(address))
+        .order_by(address_balances::token_id)
+        .select(AddressBalanceModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(balances)
+}
+
+#[derive(QueryableByName)]
+struct AffectedAddress {
+    #[diesel(sql_type = Text)]
+    address: String,
+}
+
+/// Recomputes the balances of some addresses from their unspent outputs, which keeps them
+/// right when a window is processed again.
+async fn refresh_address_balances(
+    conn: &mut diesel_async::AsyncPgConnection,
+    mut addresses: Vec<String>,
+) -> diesel::result::QueryResult<()> {
+    use crate::schema::address_balances;
+
+    addresses.sort_unstable();
+    addresses.dedup();
+    if addresses.is_empty() {
+        return Ok(());
+    }
+    diesel::delete(address_balances::table.filter(address_balances::address.eq_any(&addresses)))
+        .execute(conn)
+        .await?;
+    sql_query(
+        "INSERT INTO address_balances (address, token_id, amount) \
+         SELECT address, $2, SUM(atto_alph_amount) FROM utxos \
+         WHERE spent_tx_id IS NULL AND address = ANY($1) \
+         GROUP BY address \
+         UNION ALL \
+         SELECT address, token->>'id', SUM((token->>'amount')::NUMERIC) \
+         FROM utxos, jsonb_array_elements(tokens) AS token \
+         WHERE spent_tx_id IS NULL AND address = ANY($1) \
+         GROUP BY address, token->>'id'",
+    )
+    .bind::<Array<Text>, _>(&addresses)
+    .bind::<Text, _>(ALPH_TOKEN_ID)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::block_and_events;
+    use serde_json::json;
+
+    const TOKEN_ID: &str = "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800";
+
+    fn block(hash: &str, main_chain: bool, transactions: serde_json::Value) -> BlockAndEvents {
+        block_and_events(hash, main_chain, 1735689600000, transactions, json!([]))
+    }
+
+    fn transfer_tx() -> serde_json::Value {
+        json!([{
+            "unsigned": {
+                "txId": "tx1",
+                "version": 0,
+                "networkId": 0,
+                "gasAmount": 20000,
+                "gasPrice": "100000000000",
+                "inputs": [
+                    { "outputRef": { "hint": 1, "key": "spent_asset" }, "unlockScript": "00" }
+                ],
+                "fixedOutputs": [{
+                    "hint": 2,
+                    "key": "fixed",
+                    "attoAlphAmount": "1000000000000000000",
+                    "address": "alice",
+                    "tokens": [{ "id": TOKEN_ID, "amount": "42" }],
+                    "lockTime": 1735689600000i64,
+                    "message": ""
+                }]
+            },
+            "scriptExecutionOk": true,
+            "contractInputs": [{ "hint": 3, "key": "spent_contract" }],
+            "generatedOutputs": [
+                {
+                    "type": "ContractOutput",
+                    "hint": 4,
+                    "key": "contract",
+                    "attoAlphAmount": "100000000000000000",
+                    "address": "pool",
+                    "tokens": []
+                },
+                {
+                    "type": "AssetOutput",
+                    "hint": 5,
+                    "key": "generated",
+                    "attoAlphAmount": "5",
+                    "address": "bob",
+                    "tokens": [],
+                    "lockTime": 0,
+                    "message": ""
+                }
+            ],
+            "inputSignatures": [],
+            "scriptSignatures": []
+        }])
+    }
+
+    #[test]
+    fn test_convert_to_utxo_changes() {
+        let blocks = vec![block("main", true, transfer_tx()), block("uncle", false, transfer_tx())];
+        let (created, spent) = convert_to_utxo_changes(blocks).unwrap();
+
+        // Only the main chain block is applied
+        let keys: Vec<_> = created.iter().map(|utxo| utxo.key.as_str()).collect();
+        assert_eq!(keys, vec!["fixed", "contract", "generated"]);
+        assert!(created.iter().all(|utxo| utxo.block_hash == "main" && utxo.tx_id == "tx1"));
+        assert_eq!(created.iter().map(|utxo| utxo.output_index).collect::<Vec<_>>(), vec![0, 1, 2]);
+
+        assert_eq!(created[0].address, "alice");
+        assert_eq!(created[0].atto_alph_amount, BigDecimal::from(1_000_000_000_000_000_000u64));
+        assert_eq!(created[0].tokens, json!([{ "id": TOKEN_ID, "amount": "42" }]));
+        assert_eq!(created[0].lock_time, Some(1735689600000));
+        assert!(!created[0].is_contract);
+        assert!(created[1].is_contract);
+        assert_eq!(created[1].lock_time, None);
+        assert!(!created[2].is_contract);
+
+        let spent_keys: Vec<_> = spent.iter().map(|output| output.key.as_str()).collect();
+        assert_eq!(spent_keys, vec!["spent_asset", "spent_contract"]);
+        assert!(spent.iter().all(|output| output.tx_id == "tx1" && output.block_hash == "main"));
+    }
+
+    #[test]
+    fn test_convert_to_utxo_changes_invalid_amount() {
+        let mut tx = transfer_tx();
+        tx[0]["unsigned"]["fixedOutputs"][0]["tokens"][0]["amount"] = json!("not a number");
+        assert!(convert_to_utxo_changes(vec![block("main", true, tx)]).is_err());
+    }
+}
@@ -4,16 +4,20 @@ use diesel::{insert_into, query_dsl::methods::FilterDsl, SelectableHelper};
 
 use crate::{db::DbPool, models::block::BlockModel};
 use anyhow::Result;
-use diesel::ExpressionMethods;
+use diesel::{ExpressionMethods, OptionalExtension};
 
 use diesel::query_dsl::methods::SelectDsl;
 use diesel_async::RunQueryDsl;
 
-/// Insert blocks into the database.
+/// Insert blocks into the database, skipping blocks that are already stored.
 #[allow(clippy::get_first)]
 pub async fn insert_blocks_to_db(db: Arc<DbPool>, block_models: Vec<BlockModel>) -> Result<()> {
     let mut conn = db.get().await?;
-    insert_into(crate::schema::blocks::table).values(&block_models).execute(&mut conn).await?;
+    insert_into(crate::schema::blocks::table)
+        .values(&block_models)
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await?;
     tracing::info!(
         "Inserted {} blocks from {} to {}",
         block_models.len(),
@@ -37,6 +41,65 @@ pub async fn get_block_by_hash(db: Arc<DbPool>, block_hash: &str) -> Result<Opti
     Ok(block)
 }
 
+/// Returns the blocks of a list that are stored and not part of the main chain.
+pub async fn get_orphaned_block_hashes(
+    db: Arc<DbPool>,
+    block_hashes: &[String],
+) -> Result<Vec<String>> {
+    use crate::schema::blocks::dsl::*;
+
+    if block_hashes.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut conn = db.get().await?;
+    let orphaned = blocks
+        .filter(hash.eq_any(block_hashes))
+        .filter(main_chain.eq(false))
+        .select(hash)
+        .load(&mut conn)
+        .await?;
+    Ok(orphaned)
+}
+
+/// Returns the timestamp up to which every chain has at least `confirmations` main chain blocks
+/// on top of it, i.e. the smallest timestamp of the blocks `confirmations` deep in each chain.
+///
+/// Returns `None` if a chain has no stored block that deep.
+pub async fn get_confirmed_timestamp(
+    db: Arc<DbPool>,
+    confirmations: i64,
+    group_num: i64,
+) -> Result<Option<i64>> {
+    use crate::schema::blocks::dsl::*;
+
+    let mut conn = db.get().await?;
+    let mut confirmed: Option<i64> = None;
+    for from_group in 0..group_num {
+        for to_group in 0..group_num {
+            let chain = blocks
+                .filter(chain_from.eq(from_group))
+                .filter(chain_to.eq(to_group))
+                .filter(main_chain.eq(true));
+            let tip: Option<i64> = chain.select(diesel::dsl::max(height)).first(&mut conn).await?;
+            let Some(tip) = tip else {
+                return Ok(None);
+            };
+            let ts: Option<chrono::NaiveDateTime> = chain
+                .filter(height.eq(tip - confirmations))
+                .select(timestamp)
+                .first(&mut conn)
+                .await
+                .optional()?;
+            let Some(ts) = ts else {
+                return Ok(None);
+            };
+            let ts = ts.and_utc().timestamp_millis();
+            confirmed = Some(confirmed.map_or(ts, |confirmed| confirmed.min(ts)));
+        }
+    }
+    Ok(confirmed)
+}
+
 /** Fetch bloch-hashes belonging to the input chain-index at a height, ignoring/filtering-out one
  * block-hash.
  *
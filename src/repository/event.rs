@@ -1,14 +1,37 @@
 use std::sync::Arc;
 
-use diesel::insert_into;
+use diesel::{insert_into, ExpressionMethods, QueryDsl};
 
-use crate::{db::DbPool, models::event::EventModel};
+use crate::{db::DbPool, models::event::EventModel, types::BlockHash};
 use anyhow::Result;
 use diesel_async::RunQueryDsl;
 
-/// Insert events into the database.
+/// Insert events into the database, skipping events that are already stored.
 pub async fn insert_events_to_db(db: Arc<DbPool>, events: Vec<EventModel>) -> Result<()> {
     let mut conn = db.get().await?;
-    insert_into(crate::schema::events::table).values(&events).execute(&mut conn).await?;
+    insert_into(crate::schema::events::table)
+        .values(&events)
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Update main chain status of the events emitted in a list of blocks.
+pub async fn update_events_main_chain_status(
+    db: Arc<DbPool>,
+    block_hashes: &[BlockHash],
+    main_chain: bool,
+) -> Result<()> {
+    if block_hashes.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    diesel::update(
+        crate::schema::events::table.filter(crate::schema::events::block_hash.eq_any(block_hashes)),
+    )
+    .set(crate::schema::events::main_chain.eq(main_chain))
+    .execute(&mut conn)
+    .await?;
     Ok(())
 }
@@ -1,23 +1,29 @@
 pub mod block;
 pub mod event;
+pub mod processor_status;
+pub mod token;
 pub mod transaction;
 
 use std::sync::Arc;
 
 pub use block::*;
 pub use event::*;
+pub use processor_status::*;
+pub use token::*;
 pub use transaction::*;
 
 use crate::{
     db::DbPool,
-    models::{block::BlockModel, event::EventModel},
+    models::{block::BlockModel, event::EventModel, transaction::TransactionModel},
     types::BlockHash,
 };
 use anyhow::{Ok, Result};
 use diesel::insert_into;
 use diesel::query_dsl::methods::FilterDsl;
 use diesel::ExpressionMethods;
-use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
 
 /// Insert block and events into the database.
 pub async fn insert_block_and_events(
@@ -28,8 +34,16 @@ pub async fn insert_block_and_events(
     let mut conn = db.get().await?;
     conn.transaction(|conn| {
         async move {
-            insert_into(crate::schema::blocks::table).values(&block).execute(conn).await?;
-            insert_into(crate::schema::events::table).values(&events).execute(conn).await?;
+            insert_into(crate::schema::blocks::table)
+                .values(&block)
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await?;
+            insert_into(crate::schema::events::table)
+                .values(&events)
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await?;
             diesel::result::QueryResult::Ok(())
         }
         .scope_boxed()
@@ -38,7 +52,95 @@ pub async fn insert_block_and_events(
     Ok(())
 }
 
+/// Maximum number of rows per insert statement, keeping the bind parameters of a statement under
+/// the Postgres limit.
+pub const INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Inserts `rows` with one statement per `INSERT_CHUNK_SIZE` rows.
+///
+/// # Arguments
+///
+/// * `insert` - Builds the insert statement of a chunk of rows.
+///
+/// # Returns
+///
+/// The number of inserted rows.
+pub async fn insert_in_chunks<'a, R, Q>(
+    conn: &mut AsyncPgConnection,
+    rows: &'a [R],
+    insert: impl Fn(&'a [R]) -> Q,
+) -> diesel::result::QueryResult<usize>
+where
+    Q: diesel_async::methods::ExecuteDsl<AsyncPgConnection> + Send + 'a,
+{
+    let mut inserted = 0;
+    for chunk in rows.chunks(INSERT_CHUNK_SIZE) {
+        inserted += insert(chunk).execute(conn).await?;
+    }
+    diesel::result::QueryResult::Ok(inserted)
+}
+
+/// Insert blocks and their transactions into the database in a single DB transaction, skipping
+/// rows that are already stored.
+pub async fn insert_blocks_and_txs(
+    db: Arc<DbPool>,
+    blocks: Vec<BlockModel>,
+    txs: Vec<TransactionModel>,
+) -> Result<()> {
+    let mut conn = db.get().await?;
+    conn.transaction(|conn| {
+        async move {
+            insert_into(crate::schema::blocks::table)
+                .values(&blocks)
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await?;
+            insert_in_chunks(conn, &txs, |chunk| {
+                insert_into(crate::schema::transactions::table)
+                    .values(chunk)
+                    .on_conflict_do_nothing()
+            })
+            .await?;
+            diesel::result::QueryResult::Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+    Ok(())
+}
+
+/// Blocks whose main chain status was changed by [`update_main_chain`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MainChainUpdate {
+    pub orphaned: Vec<BlockHash>, // Blocks that left the main chain
+    pub adopted: Vec<BlockHash>,  // Blocks that joined the main chain
+}
+
+impl MainChainUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.orphaned.is_empty() && self.adopted.is_empty()
+    }
+
+    /// Merges a later update into this one, each block keeping the last status it was given.
+    pub fn merge(&mut self, other: MainChainUpdate) {
+        for hash in other.orphaned {
+            self.adopted.retain(|adopted| adopted != &hash);
+            if !self.orphaned.contains(&hash) {
+                self.orphaned.push(hash);
+            }
+        }
+        for hash in other.adopted {
+            self.orphaned.retain(|orphaned| orphaned != &hash);
+            if !self.adopted.contains(&hash) {
+                self.adopted.push(hash);
+            }
+        }
+    }
+}
+
 /// Update main chain status of block and transactions related to a block hash.
+///
+/// Returns the blocks whose main chain status changed.
 /// Reference: https://github.com/alephium/explorer-backend/blob/09cf587672bcc4cf1d02e927e88b2e71df08b2e1/app/src/main/scala/org/alephium/explorer/persistence/dao/BlockDao.scala#L121
 pub async fn update_main_chain(
     db: Arc<DbPool>,
@@ -46,8 +148,9 @@ pub async fn update_main_chain(
     chain_from: i64,
     chain_to: i64,
     group_num: Option<i64>,
-) -> Result<BlockHash> {
+) -> Result<MainChainUpdate> {
     let mut current_hash = block_hash;
+    let mut update = MainChainUpdate::default();
 
     loop {
         let block = get_block_by_hash(db.clone(), &current_hash).await?;
@@ -69,51 +172,88 @@ pub async fn update_main_chain(
                 .await?;
 
                 // Update any old main chain blocks to not be main chain
-                update_main_chain_status(db.clone(), block_hashes, false).await?;
+                update.merge(MainChainUpdate {
+                    orphaned: update_main_chain_status(db.clone(), block_hashes, false).await?,
+                    adopted: vec![],
+                });
 
                 // Update the given block to be main chain
-                update_main_chain_status(db.clone(), vec![current_hash.clone()], true).await?;
+                update.merge(MainChainUpdate {
+                    orphaned: vec![],
+                    adopted: update_main_chain_status(db.clone(), vec![current_hash.clone()], true)
+                        .await?,
+                });
 
-                current_hash = block.parent(group_num).unwrap();
-                continue;
+                match block.parent(group_num) {
+                    Some(parent) => current_hash = parent,
+                    None => break Ok(update),
+                }
             }
-            None => break Ok(current_hash),
+            None => break Ok(update),
         }
     }
 }
 
 /// Update main chain status of block and transactions related to a list of block hashes.
+///
+/// Returns the blocks whose status changed.
 pub async fn update_main_chain_status(
     db: Arc<DbPool>,
     block_hashes: Vec<String>,
     main_chain: bool,
-) -> Result<()> {
+) -> Result<Vec<BlockHash>> {
     let mut conn = db.get().await?;
+    let mut changed = Vec::new();
     if block_hashes.is_empty() {
-        return Ok(());
+        return Ok(changed);
     }
     for block_hash in block_hashes {
-        conn.transaction(|conn| {
-            async move {
-                diesel::update(
-                    crate::schema::blocks::table
-                        .filter(crate::schema::blocks::hash.eq(block_hash.clone())),
-                )
-                .set(crate::schema::blocks::main_chain.eq(main_chain))
-                .execute(conn)
-                .await?;
-                diesel::update(
-                    crate::schema::transactions::table
-                        .filter(crate::schema::transactions::block_hash.eq(block_hash)),
-                )
-                .set(crate::schema::transactions::main_chain.eq(main_chain))
-                .execute(conn)
-                .await?;
-                diesel::result::QueryResult::Ok(())
-            }
-            .scope_boxed()
-        })
-        .await?;
+        let updated = conn
+            .transaction(|conn| {
+                async move {
+                    let updated: Vec<String> = diesel::update(
+                        crate::schema::blocks::table
+                            .filter(crate::schema::blocks::hash.eq(block_hash.clone()))
+                            .filter(crate::schema::blocks::main_chain.ne(main_chain)),
+                    )
+                    .set(crate::schema::blocks::main_chain.eq(main_chain))
+                    .returning(crate::schema::blocks::hash)
+                    .get_results(conn)
+                    .await?;
+                    diesel::update(
+                        crate::schema::transactions::table
+                            .filter(crate::schema::transactions::block_hash.eq(block_hash)),
+                    )
+                    .set(crate::schema::transactions::main_chain.eq(main_chain))
+                    .execute(conn)
+                    .await?;
+                    diesel::result::QueryResult::Ok(updated)
+                }
+                .scope_boxed()
+            })
+            .await?;
+        changed.extend(updated);
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_main_chain_update_merge() {
+        let mut update = MainChainUpdate {
+            orphaned: vec!["a".to_string()],
+            adopted: vec!["b".to_string(), "c".to_string()],
+        };
+        update.merge(MainChainUpdate {
+            orphaned: vec!["b".to_string(), "d".to_string()],
+            adopted: vec!["a".to_string(), "c".to_string()],
+        });
+        assert_eq!(update.orphaned, vec!["b".to_string(), "d".to_string()]);
+        assert_eq!(update.adopted, vec!["c".to_string(), "a".to_string()]);
+        assert!(!update.is_empty());
+        assert!(MainChainUpdate::default().is_empty());
     }
-    Ok(())
 }
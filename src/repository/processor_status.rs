@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use diesel::{
+    insert_into, sql_types::BigInt, sql_types::Text, upsert::excluded, ExpressionMethods, QueryDsl,
+    SelectableHelper,
+};
+use diesel_async::RunQueryDsl;
+
+use crate::{db::DbPool, models::processor_status::ProcessorChainStatusModel};
+
+/// Get the last processed block of each chain for a processor.
+pub async fn get_chain_checkpoints(
+    db: Arc<DbPool>,
+    processor_name: &str,
+) -> Result<Vec<ProcessorChainStatusModel>> {
+    use crate::schema::processor_chain_status::dsl::*;
+
+    let mut conn = db.get().await?;
+    let checkpoints = processor_chain_status
+        .filter(processor.eq(processor_name))
+        .select(ProcessorChainStatusModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(checkpoints)
+}
+
+/// Move chain checkpoints forward, leaving a checkpoint untouched when it is already at a
+/// higher block.
+pub async fn advance_chain_checkpoints(
+    db: Arc<DbPool>,
+    checkpoints: &[ProcessorChainStatusModel],
+) -> Result<()> {
+    let mut conn = db.get().await?;
+    for checkpoint in checkpoints {
+        diesel::sql_query(
+            "INSERT INTO processor_chain_status \
+             (processor, chain_from, chain_to, block_hash, height, timestamp) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT (processor, chain_from, chain_to) DO UPDATE \
+             SET block_hash = excluded.block_hash, height = excluded.height, \
+             timestamp = excluded.timestamp \
+             WHERE excluded.height >= processor_chain_status.height",
+        )
+        .bind::<Text, _>(&checkpoint.processor)
+        .bind::<BigInt, _>(checkpoint.chain_from)
+        .bind::<BigInt, _>(checkpoint.chain_to)
+        .bind::<Text, _>(&checkpoint.block_hash)
+        .bind::<BigInt, _>(checkpoint.height)
+        .bind::<BigInt, _>(checkpoint.timestamp)
+        .execute(&mut conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Set chain checkpoints, even behind their current block, e.g. to roll them back to the
+/// fork point of a reorg.
+pub async fn reset_chain_checkpoints(
+    db: Arc<DbPool>,
+    checkpoints: &[ProcessorChainStatusModel],
+) -> Result<()> {
+    use crate::schema::processor_chain_status::dsl::*;
+
+    if checkpoints.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    insert_into(processor_chain_status)
+        .values(checkpoints)
+        .on_conflict((processor, chain_from, chain_to))
+        .do_update()
+        .set((
+            block_hash.eq(excluded(block_hash)),
+            height.eq(excluded(height)),
+            timestamp.eq(excluded(timestamp)),
+        ))
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+use diesel::sql_types::{Array, Text, Timestamp};
+use diesel::{insert_into, sql_query, ExpressionMethods, QueryDsl, SelectableHelper};
+
+use crate::{
+    db::DbPool,
+    models::token::{TokenModel, TokenSighting},
+    types::BlockHash,
+};
+use anyhow::Result;
+use diesel_async::RunQueryDsl;
+
+/// Insert tokens into the database, skipping tokens that are already stored.
+pub async fn insert_tokens_to_db(db: Arc<DbPool>, tokens: Vec<TokenModel>) -> Result<()> {
+    let mut conn = db.get().await?;
+    insert_into(crate::schema::tokens::table)
+        .values(&tokens)
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Set the metadata of stored tokens whose metadata couldn't be read before. Tokens whose
+/// metadata is still unknown are left as they are.
+pub async fn update_tokens_metadata(db: Arc<DbPool>, tokens: &[TokenModel]) -> Result<()> {
+    use crate::schema::tokens;
+
+    let mut conn = db.get().await?;
+    for token in tokens.iter().filter(|token| token.is_fungible.is_some()) {
+        diesel::update(
+            tokens::table
+                .filter(tokens::token_id.eq(&token.token_id))
+                .filter(tokens::is_fungible.is_null()),
+        )
+        .set((
+            tokens::name.eq(&token.name),
+            tokens::symbol.eq(&token.symbol),
+            tokens::decimals.eq(token.decimals),
+            tokens::total_supply.eq(&token.total_supply),
+            tokens::is_fungible.eq(token.is_fungible),
+        ))
+        .execute(&mut conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Get a token by id.
+pub async fn get_token(db: Arc<DbPool>, token_id: &str) -> Result<Option<TokenModel>> {
+    Ok(get_tokens(db, &[token_id.to_string()]).await?.pop())
+}
+
+/// Get the stored tokens among a list of token ids.
+pub async fn get_tokens(db: Arc<DbPool>, token_ids: &[String]) -> Result<Vec<TokenModel>> {
+    if token_ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut conn = db.get().await?;
+    let tokens = crate::schema::tokens::table
+        .filter(crate::schema::tokens::token_id.eq_any(token_ids))
+        .select(TokenModel::as_select())
+        .load(&mut conn)
+        .await?;
+    Ok(tokens)
+}
+
+/// Clear the first sighting of the tokens first seen in a list of blocks, which left the main
+/// chain. It is set again by `update_tokens_first_seen` at the next main chain sighting.
+pub async fn clear_tokens_first_seen_in(db: Arc<DbPool>, block_hashes: &[BlockHash]) -> Result<()> {
+    if block_hashes.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    diesel::update(
+        crate::schema::tokens::table
+            .filter(crate::schema::tokens::first_seen_block_hash.eq_any(block_hashes)),
+    )
+    .set((
+        crate::schema::tokens::first_seen_block_hash.eq(None::<String>),
+        crate::schema::tokens::first_seen_timestamp.eq(None::<NaiveDateTime>),
+    ))
+    .execute(&mut conn)
+    .await?;
+    Ok(())
+}
+
+/// Update the first sighting of stored tokens to the given sightings, when they are earlier or
+/// the first sighting was cleared.
+pub async fn update_tokens_first_seen(db: Arc<DbPool>, sightings: &[TokenSighting]) -> Result<()> {
+    if sightings.is_empty() {
+        return Ok(());
+    }
+    let token_ids: Vec<&str> = sightings.iter().map(|s| s.token_id.as_str()).collect();
+    let block_hashes: Vec<&str> = sightings.iter().map(|s| s.block_hash.as_str()).collect();
+    let timestamps: Vec<NaiveDateTime> = sightings.iter().map(|s| s.timestamp).collect();
+    let mut conn = db.get().await?;
+    sql_query(
+        "UPDATE tokens \
+         SET first_seen_block_hash = seen.block_hash, first_seen_timestamp = seen.timestamp \
+         FROM unnest($1, $2, $3) AS seen(token_id, block_hash, timestamp) \
+         WHERE tokens.token_id = seen.token_id \
+         AND (tokens.first_seen_timestamp IS NULL OR tokens.first_seen_timestamp > seen.timestamp)",
+    )
+    .bind::<Array<Text>, _>(token_ids)
+    .bind::<Array<Text>, _>(block_hashes)
+    .bind::<Array<Timestamp>, _>(timestamps)
+    .execute(&mut conn)
+    .await?;
+    Ok(())
+}
@@ -1,14 +1,38 @@
 use std::sync::Arc;
 
-use diesel::insert_into;
+use diesel::{insert_into, ExpressionMethods, QueryDsl};
 
-use crate::{db::DbPool, models::transaction::TransactionModel};
+use crate::{db::DbPool, models::transaction::TransactionModel, types::BlockHash};
 use anyhow::Result;
 use diesel_async::RunQueryDsl;
 
-/// Insert txs into the database.
+/// Insert txs into the database, skipping txs that are already stored.
 pub async fn insert_txs_to_db(db: Arc<DbPool>, txs: Vec<TransactionModel>) -> Result<()> {
     let mut conn = db.get().await?;
-    insert_into(crate::schema::transactions::table).values(&txs).execute(&mut conn).await?;
+    insert_into(crate::schema::transactions::table)
+        .values(&txs)
+        .on_conflict_do_nothing()
+        .execute(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Update main chain status of the transactions included in a list of blocks.
+pub async fn update_txs_main_chain_status(
+    db: Arc<DbPool>,
+    block_hashes: &[BlockHash],
+    main_chain: bool,
+) -> Result<()> {
+    if block_hashes.is_empty() {
+        return Ok(());
+    }
+    let mut conn = db.get().await?;
+    diesel::update(
+        crate::schema::transactions::table
+            .filter(crate::schema::transactions::block_hash.eq_any(block_hashes)),
+    )
+    .set(crate::schema::transactions::main_chain.eq(main_chain))
+    .execute(&mut conn)
+    .await?;
     Ok(())
 }
@@ -1,5 +1,13 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    address_balances (address, token_id) {
+        address -> Text,
+        token_id -> Text,
+        amount -> Numeric,
+    }
+}
+
 diesel::table! {
     blocks (hash) {
         hash -> Text,
@@ -19,6 +27,93 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    contract_states (address, block_hash, tx_id) {
+        address -> Text,
+        block_hash -> Text,
+        tx_id -> Text,
+        timestamp -> Timestamp,
+        code_hash -> Text,
+        imm_fields -> Jsonb,
+        mut_fields -> Jsonb,
+        asset -> Jsonb,
+    }
+}
+
+diesel::table! {
+    contracts (id) {
+        id -> Int4,
+        address -> Text,
+        contract_id -> Text,
+        parent_address -> Nullable<Text>,
+        creator_address -> Nullable<Text>,
+        code_hash -> Nullable<Text>,
+        std_interface_id -> Nullable<Text>,
+        creation_block_hash -> Text,
+        creation_tx_id -> Text,
+        creation_timestamp -> Timestamp,
+        destruction_block_hash -> Nullable<Text>,
+        destruction_tx_id -> Nullable<Text>,
+        destruction_timestamp -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    dex_liquidity_actions (block_hash, event_order) {
+        block_hash -> Text,
+        event_order -> Int4,
+        pair_address -> Text,
+        tx_id -> Text,
+        action_type -> Int2,
+        sender -> Text,
+        amount0 -> Numeric,
+        amount1 -> Numeric,
+        liquidity -> Numeric,
+        timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dex_pairs (address) {
+        address -> Text,
+        factory_address -> Text,
+        token0_id -> Text,
+        token1_id -> Text,
+        block_hash -> Text,
+        tx_id -> Text,
+        timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dex_reserves (block_hash, event_order) {
+        block_hash -> Text,
+        event_order -> Int4,
+        pair_address -> Text,
+        tx_id -> Text,
+        reserve0 -> Numeric,
+        reserve1 -> Numeric,
+        timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    dex_swaps (block_hash, event_order) {
+        block_hash -> Text,
+        event_order -> Int4,
+        pair_address -> Text,
+        tx_id -> Text,
+        trader -> Text,
+        to_address -> Text,
+        amount0_in -> Numeric,
+        amount1_in -> Numeric,
+        amount0_out -> Numeric,
+        amount1_out -> Numeric,
+        price -> Nullable<Numeric>,
+        timestamp -> Timestamp,
+    }
+}
+
 diesel::table! {
     events (id) {
         id -> Int4,
@@ -26,6 +121,9 @@ diesel::table! {
         contract_address -> Text,
         event_index -> Int4,
         fields -> Jsonb,
+        block_hash -> Text,
+        main_chain -> Bool,
+        event_order -> Int4,
     }
 }
 
@@ -37,6 +135,7 @@ diesel::table! {
         by -> Varchar,
         timestamp -> Timestamp,
         action_type -> Int2,
+        block_hash -> Text,
     }
 }
 
@@ -51,6 +150,66 @@ diesel::table! {
         interest_rate -> Numeric,
         duration -> Numeric,
         lender -> Varchar,
+        block_hash -> Text,
+    }
+}
+
+diesel::table! {
+    nft_collections (address) {
+        address -> Text,
+        contract_id -> Text,
+        std_interface_id -> Text,
+        collection_uri -> Nullable<Text>,
+        total_supply -> Nullable<Numeric>,
+        creation_block_hash -> Text,
+        creation_tx_id -> Text,
+        creation_timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    nft_movements (token_id, block_hash, tx_id) {
+        token_id -> Text,
+        block_hash -> Text,
+        tx_id -> Text,
+        tx_index -> Int4,
+        to_address -> Text,
+        timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    nft_owners (token_id) {
+        token_id -> Text,
+        owner_address -> Text,
+        block_hash -> Text,
+        tx_id -> Text,
+        timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    nfts (token_id) {
+        token_id -> Text,
+        address -> Text,
+        collection_address -> Nullable<Text>,
+        nft_index -> Nullable<Numeric>,
+        token_uri -> Nullable<Text>,
+        minted_block_hash -> Text,
+        minted_tx_id -> Text,
+        minted_timestamp -> Timestamp,
+    }
+}
+
+diesel::table! {
+    processor_chain_status (processor, chain_from, chain_to) {
+        #[max_length = 50]
+        processor -> Varchar,
+        chain_from -> Int8,
+        chain_to -> Int8,
+        block_hash -> Text,
+        height -> Int8,
+        timestamp -> Int8,
     }
 }
 
@@ -63,7 +222,21 @@ diesel::table! {
 }
 
 diesel::table! {
-    transactions (tx_hash) {
+    tokens (token_id) {
+        token_id -> Text,
+        contract_address -> Nullable<Text>,
+        name -> Nullable<Text>,
+        symbol -> Nullable<Text>,
+        decimals -> Nullable<Int4>,
+        total_supply -> Nullable<Numeric>,
+        is_fungible -> Nullable<Bool>,
+        first_seen_block_hash -> Nullable<Text>,
+        first_seen_timestamp -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    transactions (tx_hash, block_hash) {
         tx_hash -> Text,
         unsigned -> Jsonb,
         script_execution_ok -> Bool,
@@ -75,14 +248,72 @@ diesel::table! {
         updated_at -> Nullable<Timestamptz>,
         main_chain -> Bool,
         block_hash -> Text,
+        block_timestamp -> Timestamp,
+        gas_amount -> Int4,
+        gas_price -> Numeric,
+    }
+}
+
+diesel::table! {
+    transfer_outputs (key) {
+        key -> Text,
+        address -> Text,
+        atto_alph_amount -> Numeric,
+        tokens -> Jsonb,
+        block_hash -> Text,
+    }
+}
+
+diesel::table! {
+    transfers (tx_id, block_hash, to_address, token_id) {
+        tx_id -> Text,
+        block_hash -> Text,
+        timestamp -> Timestamp,
+        from_addresses -> Array<Text>,
+        to_address -> Text,
+        token_id -> Text,
+        amount -> Numeric,
+    }
+}
+
+diesel::table! {
+    utxos (key) {
+        key -> Text,
+        tx_id -> Text,
+        output_index -> Int4,
+        block_hash -> Text,
+        block_timestamp -> Timestamp,
+        address -> Text,
+        atto_alph_amount -> Numeric,
+        tokens -> Jsonb,
+        lock_time -> Nullable<Int8>,
+        is_contract -> Bool,
+        spent_tx_id -> Nullable<Text>,
+        spent_block_hash -> Nullable<Text>,
     }
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
+    address_balances,
     blocks,
+    contract_states,
+    contracts,
+    dex_liquidity_actions,
+    dex_pairs,
+    dex_reserves,
+    dex_swaps,
     events,
     loan_actions,
     loan_details,
+    nft_collections,
+    nft_movements,
+    nft_owners,
+    nfts,
+    processor_chain_status,
     processor_status,
+    tokens,
     transactions,
+    transfer_outputs,
+    transfers,
+    utxos,
 );
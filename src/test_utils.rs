@@ -0,0 +1,71 @@
+use serde_json::json;
+
+use crate::types::BlockAndEvents;
+
+/// Returns a block of chain `0 -> 0` with its transactions and events, in the shape of the node
+/// responses. The header values that processors don't read are made up.
+pub(crate) fn block_and_events(
+    hash: &str,
+    main_chain: bool,
+    timestamp: i64,
+    transactions: serde_json::Value,
+    events: serde_json::Value,
+) -> BlockAndEvents {
+    serde_json::from_value(json!({
+        "block": {
+            "hash": hash,
+            "parent": "parent",
+            "mainChain": main_chain,
+            "timestamp": timestamp,
+            "chainFrom": 0,
+            "chainTo": 0,
+            "height": 1000,
+            "deps": [],
+            "transactions": transactions,
+            "nonce": "nonce",
+            "version": 0,
+            "depStateHash": "dep_state_hash",
+            "txsHash": "txs_hash",
+            "target": "target",
+            "ghostUncles": []
+        },
+        "events": events
+    }))
+    .unwrap()
+}
+
+/// Serves every request with the response given by `respond` for the request path.
+pub(crate) async fn serve(respond: fn(&str) -> (u16, &'static str)) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buf = vec![0; 4096];
+            let n = stream.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let path = request.split_whitespace().nth(1).unwrap_or("").to_string();
+            let (status, body) = respond(&path);
+            let response = format!(
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+    base_url
+}
+
+/// Answers the health checks of the client like a synced node.
+pub(crate) fn healthy_node(path: &str) -> Option<(u16, &'static str)> {
+    if path.starts_with("/infos/self-clique") {
+        Some((200, r#"{"cliqueId": "clique", "selfReady": true, "synced": true}"#))
+    } else if path.starts_with("/blockflow/chain-info") {
+        Some((200, r#"{"currentHeight": 100}"#))
+    } else {
+        None
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
 pub const DEFAULT_GROUP_NUM: i64 = 4;
+
+/// Token id under which ALPH balances are stored.
+pub const ALPH_TOKEN_ID: &str = "0000000000000000000000000000000000000000000000000000000000000000";
 pub const REORG_TIMEOUT: i64 = 210 * 16 * 1000; // 210 blocks * 16 seconds
 
 pub type Event = ContractEventByBlockHash;
@@ -70,6 +73,7 @@ pub enum EventFieldType {
     U256,
     ByteVec,
     Address,
+    Array,
 }
 
 // Parsing event fields helper
@@ -77,7 +81,18 @@ pub enum EventFieldType {
 pub struct EventField {
     #[serde(rename = "type")]
     pub field_type: EventFieldType,
-    pub value: String,
+    pub value: serde_json::Value, // A JSON boolean for `Bool`, an array for `Array`, a string otherwise.
+}
+
+impl EventField {
+    /// Returns the value as text: strings as is, `Bool` and `Array` values as their JSON
+    /// encoding.
+    pub fn value_string(&self) -> String {
+        match &self.value {
+            serde_json::Value::String(value) => value.clone(),
+            value => value.to_string(),
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -134,12 +149,50 @@ pub struct OutputRef {
     pub key: String, // The key for the output reference.
 }
 
-#[derive(Deserialize, Debug, Clone, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Output {}
+/// Represents an output generated by a transaction, tagged by the node's `type` field.
+#[derive(Deserialize, Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum Output {
+    AssetOutput(AssetOutput),
+    ContractOutput(ContractOutput),
+}
+
+impl Output {
+    /// The key identifying the output, referenced by the inputs spending it.
+    pub fn key(&self) -> &str {
+        match self {
+            Output::AssetOutput(output) => &output.key,
+            Output::ContractOutput(output) => &output.key,
+        }
+    }
+
+    /// The address the output is locked to.
+    pub fn address(&self) -> &str {
+        match self {
+            Output::AssetOutput(output) => &output.address,
+            Output::ContractOutput(output) => &output.address,
+        }
+    }
+
+    /// The amount of ALPH of the output, in atto alph.
+    pub fn atto_alph_amount(&self) -> &str {
+        match self {
+            Output::AssetOutput(output) => &output.atto_alph_amount,
+            Output::ContractOutput(output) => &output.atto_alph_amount,
+        }
+    }
+
+    /// The tokens of the output.
+    pub fn tokens(&self) -> &[Token] {
+        match self {
+            Output::AssetOutput(output) => &output.tokens,
+            Output::ContractOutput(output) => &output.tokens,
+        }
+    }
+}
 
 /// Represents a contract output in a transaction.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct ContractOutput {
     pub hint: i32,                // The hint for the contract output.
@@ -147,7 +200,6 @@ pub struct ContractOutput {
     pub atto_alph_amount: String, // The amount of atto alph associated with the output.
     pub address: String,          // The address associated with the output.
     pub tokens: Vec<Token>,       // The list of tokens associated with the output.
-    pub typ: String,              // The type of the contract output.
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -158,24 +210,24 @@ pub struct AssetInput {
 }
 
 /// Represents an asset output in a transaction.
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct AssetOutput {
-    pub hint: i32,
-    pub key: String,
-    pub atto_alph_amount: String,
-    pub address: String,
-    pub tokens: Vec<Token>,
-    pub lock_time: i64,
-    pub message: String,
-    pub typ: String,
+    pub hint: i32,                // The hint for the asset output.
+    pub key: String,              // The key for the asset output.
+    pub atto_alph_amount: String, // The amount of atto alph associated with the output.
+    pub address: String,          // The address associated with the asset output.
+    pub tokens: Vec<Token>,       // The tokens associated with the output.
+    pub lock_time: i64,           // The lock time for the asset output.
+    pub message: String,          // The message for the asset output.
 }
 
-#[derive(Deserialize, Debug, Clone, Serialize)]
+/// Represents an amount of a token, the amount being a U256 kept as its decimal string.
+#[derive(Deserialize, Debug, Clone, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Token {
-    pub id: String,
-    pub amount: String,
+    pub id: String,     // The id of the token.
+    pub amount: String, // The amount of the token.
 }
 
 #[derive(Deserialize, Debug, Clone, Serialize)]
@@ -190,6 +242,257 @@ pub struct FixedAssetOutput {
     pub message: String,          // The message for the fixed asset output.
 }
 
+impl From<FixedAssetOutput> for AssetOutput {
+    fn from(output: FixedAssetOutput) -> Self {
+        AssetOutput {
+            hint: output.hint,
+            key: output.key,
+            atto_alph_amount: output.atto_alph_amount,
+            address: output.address,
+            tokens: output.tokens,
+            lock_time: output.lock_time,
+            message: output.message,
+        }
+    }
+}
+
+/// Represents the current height of a chain index.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainInfo {
+    pub current_height: i64,
+}
+
+/// Represents the block hashes of a chain index at a given height.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashesAtHeight {
+    pub headers: Vec<BlockHash>,
+}
+
+/// Represents the address and ports of a node of a clique.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerAddress {
+    pub address: String,
+    pub rest_port: i32,
+    pub ws_port: i32,
+    pub miner_api_port: i32,
+}
+
+/// Represents the clique the queried node belongs to, as returned by `/infos/self-clique`.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfClique {
+    pub clique_id: String,
+    #[serde(default)]
+    pub nodes: Vec<PeerAddress>,
+    pub self_ready: bool, // Whether the node is ready to serve requests.
+    pub synced: bool,     // Whether the node is synced with the rest of the network.
+}
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    pub release_version: String,
+    pub commit: String,
+}
+
+/// Represents the address a node advertises to its peers.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalAddress {
+    pub addr: String,
+    pub port: i32,
+}
+
+/// Represents the information returned by `/infos/node`.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfo {
+    pub build_info: BuildInfo,
+    pub upnp: bool,
+    pub external_address: Option<ExternalAddress>,
+}
+
+/// Represents the version returned by `/infos/version`.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeVersion {
+    pub version: String,
+}
+
+/// Represents the balance of an address.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Balance {
+    pub balance: String,             // The total amount of atto alph.
+    pub balance_hint: String,        // The total amount in human readable format.
+    pub locked_balance: String,      // The amount of atto alph that is time-locked.
+    pub locked_balance_hint: String, // The locked amount in human readable format.
+    #[serde(default)]
+    pub token_balances: Vec<Token>, // The total amounts of each token.
+    #[serde(default)]
+    pub locked_token_balances: Vec<Token>, // The time-locked amounts of each token.
+    pub utxo_num: i32,               // The number of UTXOs owned by the address.
+    pub warning: Option<String>,
+}
+
+/// Represents an unspent output owned by an address.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Utxo {
+    #[serde(rename = "ref")]
+    pub output_ref: OutputRef, // The reference of the output.
+    pub amount: String, // The amount of atto alph of the output.
+    #[serde(default)]
+    pub tokens: Vec<Token>, // The tokens held by the output.
+    pub lock_time: Option<i64>,
+    pub additional_data: Option<String>,
+}
+
+/// Represents the unspent outputs of an address.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Utxos {
+    pub utxos: Vec<Utxo>,
+    pub warning: Option<String>,
+}
+
+/// Represents the assets held by a contract.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetState {
+    pub atto_alph_amount: String,
+    #[serde(default)]
+    pub tokens: Vec<Token>,
+}
+
+/// Represents the state of a contract as returned by `/contracts/{address}/state`.
+/// Contract fields share the `{ "type", "value" }` encoding of event fields.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractState {
+    pub address: String,
+    pub bytecode: String,
+    pub code_hash: String,
+    pub initial_state_hash: Option<String>,
+    pub imm_fields: Vec<EventField>, // The immutable fields of the contract.
+    pub mut_fields: Vec<EventField>, // The mutable fields of the contract.
+    pub asset: AssetState,
+}
+
+/// Represents an event emitted by a contract, as returned by `/events/contract/{address}`.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractEvent {
+    pub block_hash: BlockHash,
+    pub tx_id: String,
+    pub event_index: i32,
+    pub fields: Vec<EventField>,
+}
+
+/// Represents a page of events emitted by a contract.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractEvents {
+    pub events: Vec<ContractEvent>,
+    pub next_start: i32, // The counter to pass as `start` to fetch the next page.
+}
+
+/// Represents an event emitted by a transaction, as returned by `/events/tx-id/{txId}`.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractEventByTxId {
+    pub block_hash: BlockHash,
+    pub contract_address: String,
+    pub event_index: i32,
+    pub fields: Vec<EventField>,
+}
+
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractEventsByTxId {
+    pub events: Vec<ContractEventByTxId>,
+    pub next_start: i32,
+}
+
+/// Represents a read-only call of a contract method, as sent to `/contracts/call-contract`.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallContract {
+    pub group: i32,
+    pub address: String,
+    pub method_index: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<EventField>, // Arguments share the `{ "type", "value" }` encoding of fields.
+}
+
+/// Represents the outcome of a contract call, keyed on the node's `type` field.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum CallContractResult {
+    #[serde(rename_all = "camelCase")]
+    CallContractSucceeded {
+        returns: Vec<EventField>,
+        gas_used: i32,
+    },
+    CallContractFailed {
+        error: String,
+    },
+}
+
+impl CallContractResult {
+    /// The values returned by the call, if it succeeded.
+    pub fn returns(&self) -> Option<&[EventField]> {
+        match self {
+            CallContractResult::CallContractSucceeded { returns, .. } => Some(returns),
+            CallContractResult::CallContractFailed { .. } => None,
+        }
+    }
+}
+
+/// Represents the outcomes of the calls sent to `/contracts/multicall-contract`, in order.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultipleCallContractResult {
+    pub results: Vec<CallContractResult>,
+}
+
+/// Represents the status of a transaction, keyed on the node's `type` field.
+#[derive(Deserialize, Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum TxStatus {
+    #[serde(rename_all = "camelCase")]
+    Confirmed {
+        block_hash: BlockHash,
+        tx_index: i32,
+        chain_confirmations: i32,
+        from_group_confirmations: i32,
+        to_group_confirmations: i32,
+    },
+    MemPooled {},
+    TxNotFound {},
+}
+
+/// Represents a transaction that is not part of a block yet.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTemplate {
+    pub unsigned: UnsignedTx,
+    pub input_signatures: Vec<String>,
+    pub script_signatures: Vec<String>,
+}
+
+/// Represents the transactions in the mempool of a chain index.
+#[derive(Deserialize, Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolTransactions {
+    pub from_group: i64,
+    pub to_group: i64,
+    pub transactions: Vec<TransactionTemplate>,
+}
+
 #[derive(Deserialize, Debug, Clone, Serialize)]
 pub struct TimestampRange {
     pub from: u64,
@@ -199,6 +502,7 @@ pub struct TimestampRange {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client::{Client, Network};
     use serde_json::json;
 
     /// Tests the display functionality for Hash and BlockHash types.
@@ -288,6 +592,108 @@ mod tests {
         assert_eq!(transaction.unsigned.version, 1);
     }
 
+    #[test]
+    fn test_output_serde_round_trip() {
+        // Outputs in the shape of the node `generatedOutputs`, the values are made up
+        let json_data = json!([
+            {
+                "type": "AssetOutput",
+                "hint": -1249512009,
+                "key": "c7ae3c27eed8e6d6e7aeb1d0b5e2ad6b8dd6fa6a1b9a4f4c1c2e9c0f4fb1d8e2",
+                "attoAlphAmount": "1000000000000000000",
+                "address": "1DrDyTr9RpRsQnDnXo2YRiPzPW4ooHX5LLoqXrqfMrpQH",
+                "tokens": [
+                    {
+                        "id": "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666800",
+                        "amount": "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+                    }
+                ],
+                "lockTime": 1735689600000i64,
+                "message": ""
+            },
+            {
+                "type": "ContractOutput",
+                "hint": 1547339093,
+                "key": "4d2fa2c6e1b3a2f0d8c7b6a5948372615f4e3d2c1b0a99887766554433221100",
+                "attoAlphAmount": "100000000000000000",
+                "address": "yuF1Sum4ricLFBc86h3RdjFsebR7ZXKBHm2S5sZmVsiF",
+                "tokens": []
+            }
+        ]);
+
+        let outputs: Vec<Output> = serde_json::from_value(json_data.clone()).unwrap();
+
+        let Output::AssetOutput(asset) = &outputs[0] else {
+            panic!("Expected an asset output, got {:?}", outputs[0]);
+        };
+        assert_eq!(asset.lock_time, 1735689600000);
+        assert_eq!(
+            asset.tokens[0].amount,
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert!(matches!(outputs[1], Output::ContractOutput(_)));
+        assert_eq!(outputs[1].address(), "yuF1Sum4ricLFBc86h3RdjFsebR7ZXKBHm2S5sZmVsiF");
+        assert_eq!(outputs[1].atto_alph_amount(), "100000000000000000");
+        assert!(outputs[1].tokens().is_empty());
+
+        assert_eq!(serde_json::to_value(&outputs).unwrap(), json_data);
+    }
+
+    /// Round-trips the `generatedOutputs` of mainnet blocks, walking down the chains from their
+    /// tip until both asset outputs and contract outputs holding tokens were seen. Needs a
+    /// mainnet node, `MAINNET_NODE_URL` selects another one than the public node.
+    #[tokio::test]
+    #[ignore = "needs a mainnet node"]
+    async fn test_output_serde_round_trip_mainnet() {
+        let network = Network::Mainnet;
+        let client = Client::new(network.clone()).unwrap();
+        let (mut asset_outputs, mut contract_outputs_with_tokens) = (0, 0);
+        'chains: for (chain_from, chain_to) in
+            (0..4).flat_map(|from| (0..4).map(move |to| (from, to)))
+        {
+            let tip = client.get_chain_info(chain_from, chain_to).await.unwrap().current_height;
+            for height in (tip - 100..tip).rev() {
+                let hashes =
+                    client.get_hashes_at_height(chain_from, chain_to, height).await.unwrap();
+                let url = format!("{}/blockflow/blocks/{}", network.base_url(), hashes.headers[0]);
+                let block: serde_json::Value =
+                    reqwest::get(url).await.unwrap().json().await.unwrap();
+                for tx in block["transactions"].as_array().unwrap() {
+                    let json_data = &tx["generatedOutputs"];
+                    let outputs: Vec<Output> = serde_json::from_value(json_data.clone()).unwrap();
+                    assert_eq!(serde_json::to_value(&outputs).unwrap(), *json_data);
+                    for output in &outputs {
+                        match output {
+                            Output::AssetOutput(_) => asset_outputs += 1,
+                            Output::ContractOutput(_) if !output.tokens().is_empty() => {
+                                contract_outputs_with_tokens += 1
+                            }
+                            Output::ContractOutput(_) => {}
+                        }
+                    }
+                }
+                if asset_outputs > 0 && contract_outputs_with_tokens > 0 {
+                    break 'chains;
+                }
+            }
+        }
+        assert!(asset_outputs > 0, "No asset output found");
+        assert!(contract_outputs_with_tokens > 0, "No contract output holding tokens found");
+    }
+
+    #[test]
+    fn test_output_deser_unknown_type() {
+        let json_data = json!({
+            "type": "UnknownOutput",
+            "hint": 0,
+            "key": "00",
+            "attoAlphAmount": "0",
+            "address": "address",
+            "tokens": []
+        });
+        assert!(serde_json::from_value::<Output>(json_data).is_err());
+    }
+
     #[test]
     fn test_blocks_and_events_deser() {
         let json_data = json!({
@@ -325,6 +731,81 @@ mod tests {
         assert_eq!(block_and_event.block.hash, "blockhash123");
     }
 
+    #[test]
+    fn test_tx_status_deser() {
+        let status: TxStatus = serde_json::from_value(json!({
+            "type": "Confirmed",
+            "blockHash": "blockhash123",
+            "txIndex": 0,
+            "chainConfirmations": 3,
+            "fromGroupConfirmations": 2,
+            "toGroupConfirmations": 1
+        }))
+        .unwrap();
+        assert_eq!(
+            status,
+            TxStatus::Confirmed {
+                block_hash: "blockhash123".to_string(),
+                tx_index: 0,
+                chain_confirmations: 3,
+                from_group_confirmations: 2,
+                to_group_confirmations: 1,
+            }
+        );
+
+        let status: TxStatus = serde_json::from_value(json!({ "type": "MemPooled" })).unwrap();
+        assert_eq!(status, TxStatus::MemPooled {});
+
+        let status: TxStatus = serde_json::from_value(json!({ "type": "TxNotFound" })).unwrap();
+        assert_eq!(status, TxStatus::TxNotFound {});
+    }
+
+    #[test]
+    fn test_balance_and_utxos_deser() {
+        let balance: Balance = serde_json::from_value(json!({
+            "balance": "1000000000000000000",
+            "balanceHint": "1 ALPH",
+            "lockedBalance": "0",
+            "lockedBalanceHint": "0 ALPH",
+            "tokenBalances": [{ "id": "token1", "amount": "42" }],
+            "utxoNum": 2
+        }))
+        .unwrap();
+        assert_eq!(balance.balance, "1000000000000000000");
+        assert_eq!(balance.token_balances.len(), 1);
+        assert!(balance.locked_token_balances.is_empty());
+        assert_eq!(balance.utxo_num, 2);
+
+        let utxos: Utxos = serde_json::from_value(json!({
+            "utxos": [{
+                "ref": { "hint": 1, "key": "key1" },
+                "amount": "1000",
+                "lockTime": 1672531200000i64
+            }]
+        }))
+        .unwrap();
+        assert_eq!(utxos.utxos[0].output_ref.key, "key1");
+        assert_eq!(utxos.utxos[0].lock_time, Some(1672531200000));
+    }
+
+    #[test]
+    fn test_contract_state_deser() {
+        let state: ContractState = serde_json::from_value(json!({
+            "address": "yuF1Sum4ricLFBc86h3RdjFsebR7ZXKBHm2S5sZmVsiF",
+            "bytecode": "0101",
+            "codeHash": "codehash",
+            "initialStateHash": "statehash",
+            "immFields": [{ "type": "ByteVec", "value": "00" }],
+            "mutFields": [{ "type": "U256", "value": "10" }, { "type": "Bool", "value": true }],
+            "asset": { "attoAlphAmount": "1000000000000000000" }
+        }))
+        .unwrap();
+        assert_eq!(state.code_hash, "codehash");
+        assert_eq!(state.imm_fields[0].field_type, EventFieldType::ByteVec);
+        assert_eq!(state.mut_fields[1].value, serde_json::Value::Bool(true));
+        assert!(state.asset.tokens.is_empty());
+    }
+
     #[test]
     fn test_event_deser() {
         let json_data = json!(
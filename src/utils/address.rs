@@ -0,0 +1,83 @@
+use blake2::{digest::consts::U32, Blake2b, Digest};
+
+const P2PKH_PREFIX: u8 = 0x00;
+const CONTRACT_PREFIX: u8 = 0x03;
+const P2PKH_UNLOCK_SCRIPT: u8 = 0x00;
+const PUBLIC_KEY_LENGTH: usize = 33;
+const CONTRACT_ID_LENGTH: usize = 32;
+
+// Convert a base58 contract address to its hex contract id
+pub fn contract_id_from_address(address: &str) -> Option<String> {
+    let bytes = bs58::decode(address).into_vec().ok()?;
+    match bytes.split_first() {
+        Some((&CONTRACT_PREFIX, id)) if id.len() == CONTRACT_ID_LENGTH => Some(hex::encode(id)),
+        _ => None,
+    }
+}
+
+// Convert a hex contract id, which is also the id of the token the contract may issue, to the
+// base58 contract address
+pub fn address_from_contract_id(contract_id: &str) -> Option<String> {
+    let id = hex::decode(contract_id).ok().filter(|id| id.len() == CONTRACT_ID_LENGTH)?;
+    Some(bs58::encode([&[CONTRACT_PREFIX], id.as_slice()].concat()).into_string())
+}
+
+// Get the group of the contract with a hex contract id, which is given by its last byte
+pub fn group_of_contract_id(contract_id: &str, group_num: i64) -> Option<i64> {
+    let id = hex::decode(contract_id).ok().filter(|id| id.len() == CONTRACT_ID_LENGTH)?;
+    Some(i64::from(*id.last()?) % group_num)
+}
+
+// Derive the address spending an input from its hex unlock script, only possible for P2PKH
+// unlock scripts as the other kinds don't carry every data the address is hashed from
+pub fn address_from_unlock_script(unlock_script: &str) -> Option<String> {
+    let bytes = hex::decode(unlock_script).ok()?;
+    match bytes.split_first() {
+        Some((&P2PKH_UNLOCK_SCRIPT, public_key)) if public_key.len() == PUBLIC_KEY_LENGTH => {
+            let hash = Blake2b::<U32>::digest(public_key);
+            Some(bs58::encode([&[P2PKH_PREFIX], hash.as_slice()].concat()).into_string())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT_ID: &str = "25469eb0d0d0a55deea832924547b7b166c70a3554fe321e81886d3c18f19d64";
+    const CONTRACT_ADDRESS: &str = "wCTeteGBeSEC54GpkS8jWBzYiYNTBUuTW3WzxGd9yExT";
+
+    #[test]
+    fn test_contract_id_and_address() {
+        assert_eq!(address_from_contract_id(CONTRACT_ID).as_deref(), Some(CONTRACT_ADDRESS));
+        assert_eq!(contract_id_from_address(CONTRACT_ADDRESS).as_deref(), Some(CONTRACT_ID));
+
+        // Not a contract address
+        assert_eq!(contract_id_from_address("1FQuaJLe6BAcHYoQdhW2TMENVpRgGS8rpMtLRsy9ZjbPq"), None);
+        assert_eq!(contract_id_from_address("not base58 0OIl"), None);
+        assert_eq!(address_from_contract_id("25469e"), None);
+
+        assert_eq!(group_of_contract_id(CONTRACT_ID, 4), Some(0));
+        assert_eq!(
+            group_of_contract_id(
+                "1a281053ba8601a658368594da034c2e99a0fb951b86498d05e76aedfe666803",
+                4
+            ),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_address_from_unlock_script() {
+        let public_key = "0381818e63bd9e35a5489b52a430accefc608fd60aa2c7c0d1b393b5239aedf6b2";
+        assert_eq!(
+            address_from_unlock_script(&format!("00{}", public_key)).as_deref(),
+            Some("1FQuaJLe6BAcHYoQdhW2TMENVpRgGS8rpMtLRsy9ZjbPq")
+        );
+        // Same as previous input
+        assert_eq!(address_from_unlock_script("03"), None);
+        // P2SH
+        assert_eq!(address_from_unlock_script(&format!("02{}", public_key)), None);
+    }
+}
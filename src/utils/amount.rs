@@ -0,0 +1,9 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use bigdecimal::BigDecimal;
+
+// Parse an amount returned by the node, in its smallest unit
+pub fn parse_amount(amount: &str) -> Result<BigDecimal> {
+    BigDecimal::from_str(amount).with_context(|| format!("Invalid amount {}", amount))
+}
@@ -1,3 +1,9 @@
+pub mod address;
+pub mod amount;
+pub mod signal;
 pub mod time;
 
+pub use address::*;
+pub use amount::*;
+pub use signal::*;
 pub use time::*;
@@ -1,32 +1,248 @@
-use anyhow::{Context, Result};
-use diesel::{insert_into, ExpressionMethods, OptionalExtension, QueryDsl};
+use anyhow::{anyhow, Context, Result};
+use diesel::{insert_into, sql_types::BigInt, ExpressionMethods, OptionalExtension, QueryDsl};
 use diesel_async::RunQueryDsl;
-use std::{sync::Arc, time::Duration};
+use futures::StreamExt;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     client::{Client, Network},
     config::ProcessorConfig,
     db::{new_db_pool, DbPool},
-    models::{block::BlockModel, convert_bwe_to_block_models},
+    models::{
+        block::BlockModel, convert_block_entry_to_block_model, convert_bwe_to_block_models,
+        processor_status::ProcessorChainStatusModel,
+    },
     processors::{
-        block_processor::BlockProcessor, default_processor::DefaultProcessor,
+        block_processor::BlockProcessor, contract_processor::ContractProcessor,
+        default_processor::DefaultProcessor, dex_processor::DexProcessor,
         event_processor::EventProcessor, lending_marketplace_processor::LendingContractProcessor,
-        Processor, ProcessorTrait,
+        nft_processor::NftProcessor, token_registry_processor::TokenRegistryProcessor,
+        token_transfer_processor::TokenTransferProcessor,
+        transaction_processor::TransactionProcessor, utxo_processor::UtxoProcessor, Processor,
+        ProcessorTrait,
+    },
+    repository::{
+        advance_chain_checkpoints, get_block_by_hash, get_chain_checkpoints,
+        get_confirmed_timestamp, get_orphaned_block_hashes, insert_blocks_to_db,
+        reset_chain_checkpoints, update_main_chain, MainChainUpdate,
     },
-    repository::{get_block_by_hash, insert_blocks_to_db, update_main_chain},
     schema::processor_status,
-    types::REORG_TIMEOUT,
+    types::{BlockAndEvents, BlockEntry, BlockHash, DEFAULT_GROUP_NUM, REORG_TIMEOUT},
+    ws::{RecentHashes, WsClient},
 };
 #[derive(Debug, Default)]
 pub struct SyncOptions {
     pub start_ts: Option<i64>,
+    /// Last timestamp to sync, inclusive. When set, the worker re-indexes
+    /// `[start_ts, end_ts]` (regardless of its checkpoint) and returns once done.
+    pub end_ts: Option<i64>,
     pub step: Option<i64>,
+    /// How far behind the saved checkpoint to resume on startup, in milliseconds, to cover
+    /// the reorg window again after a crash.
     pub back_step: Option<i64>,
     pub sync_duration: Option<i64>,
+    /// Websocket URL of the node. When set, the worker switches from polling to block
+    /// notifications once it reaches the chain tip, and falls back to polling if the socket dies.
+    pub ws_url: Option<String>,
+    /// Maximum number of windows fetched concurrently while backfilling history older than the
+    /// reorg interval. Backfilling is sequential when unset or set to 1.
+    pub backfill_concurrency: Option<usize>,
+    /// Number of blocks a sync window should return. When set, the window grows (up to
+    /// `max_step`) while responses are small and shrinks (down to `min_step`) when they are
+    /// larger than this target.
+    pub target_block_count: Option<usize>,
+    pub min_step: Option<i64>,
+    pub max_step: Option<i64>,
+    /// Maximum number of ancestors walked down to download the missing parents of a block
+    /// during reorg handling, e.g. when starting inside the reorg interval without history.
+    /// Defaults to `DEFAULT_MAX_PARENT_FETCH_DEPTH`.
+    pub max_parent_fetch_depth: Option<usize>,
+    /// Number of groups of the network, used to find the parent of a block among its deps and
+    /// the group of the contracts called by processors. Defaults to `DEFAULT_GROUP_NUM`.
+    pub group_num: Option<i64>,
+    /// How far behind the chain tip, in milliseconds, blocks may still be reorganized. Reorgs
+    /// are only handled for windows inside this interval. Defaults to `REORG_TIMEOUT`.
+    pub reorg_timeout: Option<i64>,
+    /// Finality required by each processor, by processor name. Processors not listed follow
+    /// the tip (`Finality::Latest`).
+    pub finality: HashMap<String, Finality>,
+}
+
+/// Which blocks a processor is handed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Finality {
+    /// Blocks as soon as they are fetched, reverting them through `handle_reorg` if they
+    /// leave the main chain.
+    #[default]
+    Latest,
+    /// Blocks with at least this many main chain blocks on top of them in every chain, or
+    /// older than the reorg interval.
+    Confirmations(i64),
+    /// Blocks older than this many milliseconds.
+    Window(i64),
+}
+
+impl Finality {
+    /// Returns the latest timestamp of the blocks that are final, `now` being the current
+    /// time and `confirmed_ts` the timestamp up to which every chain has the required
+    /// confirmations, if known.
+    pub fn cutoff(&self, now: i64, reorg_timeout: i64, confirmed_ts: Option<i64>) -> i64 {
+        match self {
+            Finality::Latest => i64::MAX,
+            Finality::Confirmations(_) => confirmed_ts.unwrap_or(i64::MIN).max(now - reorg_timeout),
+            Finality::Window(window) => now - window,
+        }
+    }
+}
+
+/// Default number of ancestors walked down when downloading missing parents.
+pub const DEFAULT_MAX_PARENT_FETCH_DEPTH: usize = 100;
+
+/// Size of the sync window, in milliseconds, adapted to the number of blocks returned by the node.
+#[derive(Debug, Clone)]
+pub struct AdaptiveStep {
+    step: i64,
+    min_step: i64,
+    max_step: i64,
+    target_block_count: Option<usize>,
+}
+
+impl AdaptiveStep {
+    pub fn new(sync_opts: &SyncOptions) -> Self {
+        let step = sync_opts.step.unwrap_or(1000);
+        let min_step = sync_opts.min_step.unwrap_or(100).min(step).max(1);
+        let max_step = sync_opts.max_step.unwrap_or(60 * 1000).max(step);
+        Self { step, min_step, max_step, target_block_count: sync_opts.target_block_count }
+    }
+
+    /// Returns the current window size.
+    pub fn current(&self) -> i64 {
+        self.step
+    }
+
+    /// Returns the smallest window size.
+    pub fn min(&self) -> i64 {
+        self.min_step
+    }
+
+    pub fn is_adaptive(&self) -> bool {
+        self.target_block_count.is_some()
+    }
+
+    /// Adjusts the window size to the number of blocks returned by the last window, halving it
+    /// when above the target and doubling it when below half the target.
+    ///
+    /// # Returns
+    ///
+    /// The new window size.
+    pub fn record(&mut self, block_count: usize) -> i64 {
+        let Some(target) = self.target_block_count else {
+            return self.step;
+        };
+        let previous = self.step;
+        if block_count > target {
+            self.step = (self.step / 2).max(self.min_step);
+        } else if block_count * 2 < target {
+            self.step = (self.step * 2).min(self.max_step);
+        }
+        if self.step != previous {
+            tracing::info!(
+                block_count = block_count,
+                previous_step = previous,
+                step = self.step,
+                "Adjusted sync window"
+            );
+        }
+        self.step
+    }
+}
+
+/// Returns the number of blocks in a response, across all chains.
+fn block_count(blocks: &[Vec<BlockAndEvents>]) -> usize {
+    blocks.iter().map(Vec::len).sum()
+}
+
+/// Why [`Worker::run`] returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// Shutdown was requested through the cancellation token.
+    Shutdown,
+    /// The worker synced up to `SyncOptions::end_ts`.
+    ReachedEndTimestamp,
+}
+
+/// Number of block hashes remembered in live-tail mode to skip blocks that were already polled.
+const LIVE_TAIL_DEDUP_CAPACITY: usize = 4096;
+
+/// Maximum number of windows a processor that fell behind fetches on its own per sync iteration.
+const MAX_CATCH_UP_WINDOWS: usize = 10;
+
+/// A processor hosted by the worker.
+///
+/// `next_ts` is the first timestamp the processor has not processed yet. Processors at or ahead
+/// of the worker cursor are fed the shared windows; a processor that failed a window falls
+/// behind and catches up on its own, without holding the others back.
+struct ProcessorTask {
+    processor: Processor,
+    next_ts: i64,
+    chains: HashMap<ChainIndex, ProcessorChainStatusModel>, // Last processed block of each chain
+    finality: Finality,
+}
+
+impl ProcessorTask {
+    /// Whether the processor is handed blocks as soon as they are fetched. Other processors
+    /// trail behind on their own, up to their finality cutoff.
+    fn follows_tip(&self) -> bool {
+        self.finality == Finality::Latest
+    }
+
+    /// Returns the height of the last processed block of each chain.
+    fn chain_heights(&self) -> HashMap<ChainIndex, i64> {
+        self.chains.iter().map(|(chain, checkpoint)| (*chain, checkpoint.height)).collect()
+    }
+
+    /// Records chain checkpoints that were persisted: `rollbacks` replace the current ones,
+    /// `tips` only move them forward.
+    fn record_checkpoints(
+        &mut self,
+        rollbacks: Vec<ProcessorChainStatusModel>,
+        tips: Vec<ProcessorChainStatusModel>,
+    ) {
+        for checkpoint in rollbacks {
+            self.chains.insert((checkpoint.chain_from, checkpoint.chain_to), checkpoint);
+        }
+        for checkpoint in tips {
+            let chain = (checkpoint.chain_from, checkpoint.chain_to);
+            if self.chains.get(&chain).is_none_or(|current| current.height <= checkpoint.height) {
+                self.chains.insert(chain, checkpoint);
+            }
+        }
+    }
 }
 
-/// Worker manages the lifecycle of a processor.
+/// Chain of a block, as `(chain_from, chain_to)`.
+type ChainIndex = (i64, i64);
+
+/// Blocks missing on a chain between its checkpoint and the lowest block of a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ChainGap {
+    chain: ChainIndex,
+    from_height: i64,    // First missing height
+    to_height: i64,      // Last missing height
+    top_hash: BlockHash, // Hash of the block at `to_height`
+}
+
+/// Worker manages the lifecycle of a set of processors.
+///
+/// Each window of blocks is fetched once and handed to every processor, and each processor keeps
+/// its own checkpoints: the polling cursor in `processor_status`, and the last processed block of
+/// each chain in `processor_chain_status`, used to detect and fill gaps and to roll back on reorgs.
 ///
 /// In the initialization phase, we make sure we get at least one timestamp other than the genesis one
 ///
@@ -46,7 +262,7 @@ pub struct SyncOptions {
 pub struct Worker {
     pub db_pool: Arc<DbPool>,
     pub client: Arc<Client>,
-    pub processor_config: ProcessorConfig,
+    pub processor_configs: Vec<ProcessorConfig>,
     pub db_url: String,
     pub sync_opts: SyncOptions,
 }
@@ -59,126 +275,717 @@ impl Worker {
         db_pool_size: Option<u32>,
         sync_opts: Option<SyncOptions>,
     ) -> Result<Self> {
-        let processor_name = processor_config.name();
-        tracing::info!(processor_name = processor_name, "Creating worker");
+        Self::with_processors(vec![processor_config], db_url, network, db_pool_size, sync_opts)
+            .await
+    }
+
+    /// Creates a worker hosting several processors that share the fetched blocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `processor_configs` - The processors to run. Checkpoints are keyed by processor name,
+    ///   so each name may only appear once.
+    ///
+    /// # Returns
+    ///
+    /// The worker, or an error if no processor is given, a processor name is duplicated, or
+    /// the connection pool can't be created.
+    pub async fn with_processors(
+        processor_configs: Vec<ProcessorConfig>,
+        db_url: String,
+        network: Network,
+        db_pool_size: Option<u32>,
+        sync_opts: Option<SyncOptions>,
+    ) -> Result<Self> {
+        if processor_configs.is_empty() {
+            return Err(anyhow!("A worker needs at least one processor"));
+        }
+        let processor_names = processor_names(&processor_configs);
+        for (i, config) in processor_configs.iter().enumerate() {
+            if processor_configs[..i].iter().any(|other| other.name() == config.name()) {
+                return Err(anyhow!("Processor {} is configured more than once", config.name()));
+            }
+        }
+        if let Some(sync_opts) = sync_opts.as_ref() {
+            for name in sync_opts.finality.keys() {
+                if !processor_configs.iter().any(|config| config.name() == name) {
+                    return Err(anyhow!("Finality set for unknown processor {}", name));
+                }
+            }
+        }
+        tracing::info!(processors = processor_names, "Creating worker");
 
-        tracing::info!(processor_name = processor_name, "Creating connection pool");
+        tracing::info!(processors = processor_names, "Creating connection pool");
         let db_pool =
             new_db_pool(&db_url, db_pool_size).await.context("Failed to create connection pool")?;
-        tracing::info!(processor_name = processor_name, "Finish creating the connection pool");
+        tracing::info!(processors = processor_names, "Finish creating the connection pool");
 
         let sync_opts = sync_opts.unwrap_or_default();
 
-        Ok(Self {
-            db_pool,
-            processor_config,
-            db_url,
-            sync_opts,
-            client: Arc::new(Client::new(network)),
-        })
+        let client = Client::new(network).context("Failed to create node client")?;
+
+        Ok(Self { db_pool, processor_configs, db_url, sync_opts, client: Arc::new(client) })
     }
 
-    pub async fn run(&mut self) {
-        let processor_name = self.processor_config.name();
-        tracing::info!(processor_name = processor_name, "Starting worker");
+    /// Runs the worker until `shutdown` is cancelled or, for a bounded re-index, until
+    /// `SyncOptions::end_ts` is reached by every processor.
+    ///
+    /// Cancellation is only observed between windows: the window being processed is finished
+    /// and its checkpoints persisted before returning.
+    ///
+    /// # Returns
+    ///
+    /// Why the worker stopped, or the error that stopped it (failing migrations, or the
+    /// database being unable to load or persist the checkpoint).
+    pub async fn run(&mut self, shutdown: CancellationToken) -> Result<StopReason> {
+        let processor_names = processor_names(&self.processor_configs);
+        tracing::info!(processors = processor_names, "Starting worker");
 
-        tracing::info!(processor_name = processor_name, "Run migrations");
+        tracing::info!(processors = processor_names, "Run migrations");
         let migration_time = std::time::Instant::now();
-        self.run_migrations().await;
+        self.run_migrations().await?;
         tracing::info!(
-            processor_name = processor_name,
+            processors = processor_names,
             duration_in_secs = migration_time.elapsed().as_secs_f64(),
             "Finished migrations"
         );
 
-        // Initialize sync parameters
-        let last_ts = get_last_timestamp(&self.db_pool, processor_name).await.unwrap();
-        tracing::info!(processor_name = processor_name, last_ts = last_ts, "Got last timestamp");
-        let mut current_ts = self.sync_opts.start_ts.unwrap_or(0);
-        if current_ts < last_ts {
-            current_ts = last_ts;
+        // Initialize sync parameters, each processor resumes from its own checkpoint
+        let mut tasks = Vec::with_capacity(self.processor_configs.len());
+        for config in self.processor_configs.iter() {
+            let last_ts = get_last_timestamp(&self.db_pool, config.name()).await?;
+            let next_ts = initial_timestamp(&self.sync_opts, last_ts);
+            let chains: HashMap<_, _> = get_chain_checkpoints(self.db_pool.clone(), config.name())
+                .await?
+                .into_iter()
+                .map(|checkpoint| ((checkpoint.chain_from, checkpoint.chain_to), checkpoint))
+                .collect();
+            tracing::info!(
+                processor_name = config.name(),
+                last_ts = last_ts,
+                start_ts = next_ts,
+                chain_checkpoints = chains.len(),
+                "Got last timestamp"
+            );
+            tasks.push(ProcessorTask {
+                processor: build_processor(
+                    config,
+                    self.db_pool.clone(),
+                    self.client.clone(),
+                    self.sync_opts.group_num.unwrap_or(DEFAULT_GROUP_NUM),
+                ),
+                next_ts,
+                chains,
+                finality: self.sync_opts.finality.get(config.name()).copied().unwrap_or_default(),
+            });
         }
+        // Follow the most advanced processors, the others catch up on their own
+        let mut current_ts = tasks
+            .iter()
+            .filter(|task| task.follows_tip())
+            .map(|task| task.next_ts)
+            .max()
+            .or_else(|| tasks.iter().map(|task| task.next_ts).max())
+            .unwrap_or_default();
+        let end_ts = self.sync_opts.end_ts;
+        tracing::info!(
+            processors = processor_names,
+            start_ts = current_ts,
+            end_ts = ?end_ts,
+            "Starting sync"
+        );
 
-        let step = self.sync_opts.step.unwrap_or(1000);
-        let sync_duration = Duration::from_secs(self.sync_opts.sync_duration.unwrap_or(1) as u64);
-
-        let processor = build_processor(&self.processor_config, self.db_pool.clone());
+        let mut step = AdaptiveStep::new(&self.sync_opts);
+        let sync_duration = self.sync_duration();
 
         loop {
-            let to_ts = current_ts + step;
+            if shutdown.is_cancelled() {
+                tracing::info!(processors = processor_names, "Shutdown requested, stopping");
+                return Ok(StopReason::Shutdown);
+            }
+
+            let lagging = tasks.iter().any(|task| task.next_ts < current_ts);
+            if lagging {
+                self.catch_up(&mut tasks, current_ts, step.current(), &shutdown).await;
+            }
+
+            if end_ts.is_some_and(|end_ts| current_ts > end_ts) {
+                if !lagging {
+                    tracing::info!(processors = processor_names, "Reached end timestamp, stopping");
+                    return Ok(StopReason::ReachedEndTimestamp);
+                }
+                sleep_or_shutdown(sync_duration, &shutdown).await;
+                continue;
+            }
+
+            // Far behind the tip, fetch many windows at once
+            if self.sync_opts.backfill_concurrency.unwrap_or(1) > 1 {
+                let mut backfill_to = chrono::Utc::now().timestamp_millis() - self.reorg_timeout();
+                if let Some(end_ts) = end_ts {
+                    backfill_to = backfill_to.min(end_ts);
+                }
+                if current_ts + step.current() <= backfill_to {
+                    if let Err(err) = self
+                        .backfill(&mut tasks, &mut current_ts, backfill_to, &mut step, &shutdown)
+                        .await
+                    {
+                        tracing::error!(
+                            processors = processor_names,
+                            error = ?err,
+                            "Error backfilling blocks, retrying in {:?}",
+                            sync_duration
+                        );
+                        sleep_or_shutdown(sync_duration, &shutdown).await;
+                    }
+                    continue;
+                }
+            }
+
+            let mut to_ts = current_ts + step.current();
+            if step.is_adaptive() {
+                // A grown window must not reach into the future, or the blocks mined after the
+                // request would be skipped
+                let now = chrono::Utc::now().timestamp_millis();
+                to_ts = to_ts.min(now).max(current_ts + step.min());
+            }
+            if let Some(end_ts) = end_ts {
+                to_ts = to_ts.min(end_ts);
+            }
 
             tracing::info!(
-                processor_name = processor_name,
+                processors = processor_names,
                 from_ts = current_ts,
                 to_ts = to_ts,
+                step = step.current(),
                 "Syncing blocks"
             );
             // Fetch blocks
             match self.client.get_blocks_and_events(current_ts, to_ts).await {
                 Ok(blocks) => {
+                    let count = block_count(&blocks.blocks_and_events);
                     tracing::info!(
-                        processor_name = processor_name,
-                        block_count = blocks.blocks_and_events.len(),
+                        processors = processor_names,
+                        block_count = count,
                         "Found blocks"
                     );
 
-                    // Handle reorg when inside reorg interval
-                    if chrono::Utc::now().timestamp_millis() - to_ts <= REORG_TIMEOUT {
-                        tracing::info!(
-                            processor_name = processor_name,
-                            "Inside reorg interval, handling reorg if needed",
-                        );
-                        let blocks = convert_bwe_to_block_models(blocks.blocks_and_events.clone());
-                        for block in blocks.iter() {
-                            self.insert(self.db_pool.clone(), block.clone()).await.unwrap();
-                        }
-                    }
-
-                    // Process blocks
-                    if let Err(err) =
-                        processor.process_blocks(current_ts, to_ts, blocks.blocks_and_events).await
+                    if let Err(err) = self
+                        .process_window(&mut tasks, current_ts, to_ts, blocks.blocks_and_events)
+                        .await
                     {
                         tracing::error!(
-                            processor_name = processor_name,
+                            processors = processor_names,
                             error = ?err,
                             "Error processing blocks, retrying in {:?}",
                             sync_duration
                         );
-                        sleep(sync_duration).await;
+                        sleep_or_shutdown(sync_duration, &shutdown).await;
                         continue;
                     }
-                    update_last_timestamp(&self.db_pool, processor_name, to_ts).await.unwrap();
                     current_ts = to_ts + 1;
+                    step.record(count);
+                }
+                Err(err) => {
+                    // The client already retried transient failures, honour the node's
+                    // `Retry-After` hint if it gave one before trying the window again.
+                    let retry_in = err.retry_after().unwrap_or(sync_duration);
+                    tracing::error!(
+                        processors = processor_names,
+                        error = %err,
+                        retryable = err.is_retryable(),
+                        "Error fetching blocks, retrying in {:?}",
+                        retry_in
+                    );
+                    sleep_or_shutdown(retry_in, &shutdown).await;
+                    continue;
+                }
+            }
+
+            // Once every processor following the tip reached it, follow it through block
+            // notifications
+            if let Some(ws_url) = self.sync_opts.ws_url.as_ref().filter(|_| end_ts.is_none()) {
+                let in_sync = tasks
+                    .iter()
+                    .filter(|task| task.follows_tip())
+                    .all(|task| task.next_ts >= current_ts);
+                if in_sync && current_ts >= chrono::Utc::now().timestamp_millis() {
+                    if let Err(err) = self
+                        .live_tail(&mut tasks, ws_url, &mut current_ts, step.current(), &shutdown)
+                        .await
+                    {
+                        tracing::error!(
+                            processors = processor_names,
+                            error = ?err,
+                            "Live tail stopped, falling back to polling"
+                        );
+                    }
+                }
+            }
+
+            tracing::info!(processors = processor_names, "Sleeping for {:?}", sync_duration);
+            sleep_or_shutdown(sync_duration, &shutdown).await;
+        }
+    }
+
+    /// Handles reorgs for a batch of blocks if they are inside the reorg interval, then hands
+    /// them over to every processor following the tip that is not behind `from_ts`,
+    /// concurrently, and moves their checkpoints to `to_ts`.
+    ///
+    /// Blocks missing between the processors' chain checkpoints and the window (e.g. blocks
+    /// that reached the node after the window covering their timestamp was polled) are fetched
+    /// and processed first.
+    ///
+    /// When blocks leave or join the main chain, the chain checkpoints past the fork point are
+    /// rolled back to it, and each processor's reorg hook is called with the orphaned blocks and
+    /// the blocks that replaced them.
+    ///
+    /// A processor failing the window is moved back to `from_ts` so that it catches up on its
+    /// own while the others carry on.
+    ///
+    /// # Returns
+    ///
+    /// The number of processors that failed the window, or an error if the reorg handling
+    /// failed or every processor failed, in which case the window has to be retried.
+    async fn process_window(
+        &self,
+        tasks: &mut [ProcessorTask],
+        from_ts: i64,
+        to_ts: i64,
+        mut blocks: Vec<Vec<BlockAndEvents>>,
+    ) -> Result<usize> {
+        // Fill the holes between the chain checkpoints and the window
+        let mut heights = HashMap::new();
+        for task in tasks.iter().filter(|task| task.follows_tip() && task.next_ts >= from_ts) {
+            for (chain, height) in task.chain_heights() {
+                heights
+                    .entry(chain)
+                    .and_modify(|h: &mut i64| *h = (*h).min(height))
+                    .or_insert(height);
+            }
+        }
+        let gaps = find_gaps(&heights, &blocks);
+        if !gaps.is_empty() {
+            blocks.insert(0, self.fill_gaps(&gaps).await?);
+        }
+
+        let mut reorg = MainChainUpdate::default();
+        // Handle reorg when inside reorg interval
+        if chrono::Utc::now().timestamp_millis() - to_ts <= self.reorg_timeout() {
+            tracing::info!(
+                processors = processor_names(&self.processor_configs),
+                "Inside reorg interval, handling reorg if needed",
+            );
+            let models = convert_bwe_to_block_models(blocks.clone());
+            for block in models.iter() {
+                reorg.merge(self.insert(self.db_pool.clone(), block.clone()).await?);
+            }
+        }
+        let replacements = if reorg.is_empty() {
+            vec![]
+        } else {
+            tracing::info!(
+                orphaned = reorg.orphaned.len(),
+                adopted = reorg.adopted.len(),
+                "Main chain changed, reverting orphaned blocks"
+            );
+            self.replacement_blocks(&reorg.adopted, &blocks).await?
+        };
+        let forks = self.fork_points(&reorg.orphaned).await?;
+        let tips = chain_tips(&blocks);
+
+        let mut participants: Vec<_> =
+            tasks.iter_mut().filter(|task| task.follows_tip() && task.next_ts >= from_ts).collect();
+        let results = futures::future::join_all(participants.iter().map(|task| async {
+            let processor_name = task.processor.name();
+            let rollbacks = rollback_checkpoints(&task.chains, &forks);
+            reset_chain_checkpoints(self.db_pool.clone(), &rollbacks).await?;
+            if !reorg.is_empty() {
+                task.processor.handle_reorg(&reorg.orphaned, replacements.clone()).await?;
+            }
+            task.processor.process_blocks(from_ts, to_ts, blocks.clone()).await?;
+            update_last_timestamp(&self.db_pool, processor_name, to_ts).await?;
+            let tips = checkpoints_of(processor_name, &tips);
+            advance_chain_checkpoints(self.db_pool.clone(), &tips).await?;
+            Ok::<_, anyhow::Error>((rollbacks, tips))
+        }))
+        .await;
+
+        let mut failed = 0;
+        for (task, result) in participants.iter_mut().zip(results) {
+            match result {
+                Ok((rollbacks, tips)) => {
+                    task.record_checkpoints(rollbacks, tips);
+                    task.next_ts = task.next_ts.max(to_ts + 1);
+                }
+                Err(err) => {
+                    tracing::error!(
+                        processor_name = task.processor.name(),
+                        from_ts = from_ts,
+                        to_ts = to_ts,
+                        error = ?err,
+                        "Error processing blocks, processor falls behind"
+                    );
+                    task.next_ts = task.next_ts.min(from_ts);
+                    failed += 1;
+                }
+            }
+        }
+        if failed > 0 && failed == participants.len() {
+            return Err(anyhow!("Every processor failed to process the window"));
+        }
+        Ok(failed)
+    }
+
+    /// Returns the blocks with events of `hashes`, taking them from the window when possible
+    /// and fetching the others. The blocks are marked as main chain, which they may not have
+    /// been yet when fetched.
+    async fn replacement_blocks(
+        &self,
+        hashes: &[BlockHash],
+        window: &[Vec<BlockAndEvents>],
+    ) -> Result<Vec<BlockAndEvents>> {
+        let mut replacements = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            let known = window.iter().flatten().find(|be| &be.block.hash == hash);
+            let mut be = match known {
+                Some(be) => be.clone(),
+                None => self.client.get_block_and_events_by_hash(hash).await?,
+            };
+            be.block.main_chain = true;
+            replacements.push(be);
+        }
+        Ok(replacements)
+    }
+
+    /// Downloads the blocks of `gaps`, walking down each chain from the top of the gap.
+    /// Gaps larger than `max_parent_fetch_depth` are left unfilled.
+    ///
+    /// # Returns
+    ///
+    /// The downloaded blocks, lowest first.
+    async fn fill_gaps(&self, gaps: &[ChainGap]) -> Result<Vec<BlockAndEvents>> {
+        let max_depth =
+            self.sync_opts.max_parent_fetch_depth.unwrap_or(DEFAULT_MAX_PARENT_FETCH_DEPTH);
+        let mut filled = Vec::new();
+        for gap in gaps {
+            let size = gap.to_height - gap.from_height + 1;
+            if size > max_depth as i64 {
+                tracing::warn!(
+                    chain_from = gap.chain.0,
+                    chain_to = gap.chain.1,
+                    from_height = gap.from_height,
+                    to_height = gap.to_height,
+                    "Gap larger than the parent fetch depth limit, leaving it unfilled"
+                );
+                continue;
+            }
+            tracing::info!(
+                chain_from = gap.chain.0,
+                chain_to = gap.chain.1,
+                from_height = gap.from_height,
+                to_height = gap.to_height,
+                "Filling gap in chain"
+            );
+            let mut hash = gap.top_hash.clone();
+            for _ in 0..size {
+                let be = self.client.get_block_and_events_by_hash(&hash).await?;
+                if be.block.height < gap.from_height {
+                    break;
+                }
+                hash = be.block.parent.clone();
+                filled.push(be);
+            }
+        }
+        filled.sort_by_key(|be| (be.block.chain_from, be.block.chain_to, be.block.height));
+        Ok(filled)
+    }
+
+    /// Returns the fork point of each chain with orphaned blocks: the parent of the lowest
+    /// orphaned block, which is still in the main chain.
+    async fn fork_points(&self, orphaned: &[BlockHash]) -> Result<HashMap<ChainIndex, BlockModel>> {
+        let mut lowest: HashMap<ChainIndex, BlockModel> = HashMap::new();
+        for hash in orphaned {
+            if let Some(block) = get_block_by_hash(self.db_pool.clone(), hash).await? {
+                let chain = (block.chain_from, block.chain_to);
+                if lowest.get(&chain).is_none_or(|current| block.height < current.height) {
+                    lowest.insert(chain, block);
                 }
+            }
+        }
+
+        let mut forks = HashMap::new();
+        for (chain, block) in lowest {
+            let Some(parent) = block.parent(self.sync_opts.group_num) else {
+                continue;
+            };
+            if let Some(parent) = get_block_by_hash(self.db_pool.clone(), &parent).await? {
+                forks.insert(chain, parent);
+            }
+        }
+        Ok(forks)
+    }
+
+    /// Brings the processors that fell behind `until` closer to it, fetching at most
+    /// `MAX_CATCH_UP_WINDOWS` windows for each of them. Processors catch up concurrently, and a
+    /// failure only stops the processor concerned until the next sync iteration. Processors
+    /// requiring finality are never brought past their finality cutoff.
+    ///
+    /// The reorg handling of these windows already happened when the shared window was
+    /// processed, so only the processors run, reverting the blocks of the window that since
+    /// left the main chain.
+    async fn catch_up(
+        &self,
+        tasks: &mut [ProcessorTask],
+        until: i64,
+        step: i64,
+        shutdown: &CancellationToken,
+    ) {
+        let lagging = tasks.iter_mut().filter(|task| task.next_ts < until);
+        futures::future::join_all(lagging.map(|task| async move {
+            let processor_name = task.processor.name();
+            let until = match self.finality_cutoff(task.finality).await {
+                Ok(cutoff) => until.min(cutoff.saturating_add(1)),
                 Err(err) => {
                     tracing::error!(
                         processor_name = processor_name,
                         error = ?err,
-                        "Error fetching blocks, retrying in {:?}",
-                        sync_duration
+                        "Error computing the finality cutoff, retrying at the next sync"
                     );
-                    sleep(sync_duration).await;
+                    return;
+                }
+            };
+            for _ in 0..MAX_CATCH_UP_WINDOWS {
+                if task.next_ts >= until || shutdown.is_cancelled() {
+                    break;
+                }
+                let (from, to) = (task.next_ts, (task.next_ts + step).min(until - 1));
+                tracing::info!(
+                    processor_name = processor_name,
+                    from_ts = from,
+                    to_ts = to,
+                    "Catching up"
+                );
+                let result = async {
+                    let mut blocks = self.client.get_blocks_and_events(from, to).await?;
+                    let gaps = find_gaps(&task.chain_heights(), &blocks.blocks_and_events);
+                    if !gaps.is_empty() {
+                        blocks.blocks_and_events.insert(0, self.fill_gaps(&gaps).await?);
+                    }
+                    let hashes: Vec<BlockHash> = blocks
+                        .blocks_and_events
+                        .iter()
+                        .flatten()
+                        .map(|be| be.block.hash.clone())
+                        .collect();
+                    let tips =
+                        checkpoints_of(processor_name, &chain_tips(&blocks.blocks_and_events));
+                    task.processor.process_blocks(from, to, blocks.blocks_and_events).await?;
+                    let orphaned = get_orphaned_block_hashes(self.db_pool.clone(), &hashes).await?;
+                    let mut rollbacks = vec![];
+                    if !orphaned.is_empty() {
+                        rollbacks =
+                            rollback_checkpoints(&task.chains, &self.fork_points(&orphaned).await?);
+                        reset_chain_checkpoints(self.db_pool.clone(), &rollbacks).await?;
+                        task.processor.handle_reorg(&orphaned, vec![]).await?;
+                    }
+                    update_last_timestamp(&self.db_pool, processor_name, to).await?;
+                    advance_chain_checkpoints(self.db_pool.clone(), &tips).await?;
+                    Ok::<_, anyhow::Error>((rollbacks, tips))
+                }
+                .await;
+                match result {
+                    Ok((rollbacks, tips)) => task.record_checkpoints(rollbacks, tips),
+                    Err(err) => {
+                        tracing::error!(
+                            processor_name = processor_name,
+                            error = ?err,
+                            "Error catching up, retrying at the next sync"
+                        );
+                        break;
+                    }
+                }
+                task.next_ts = to + 1;
+            }
+        }))
+        .await;
+    }
+
+    /// Returns the latest timestamp of the blocks a processor with `finality` may be handed.
+    async fn finality_cutoff(&self, finality: Finality) -> Result<i64> {
+        let confirmed_ts = match finality {
+            Finality::Confirmations(confirmations) => {
+                let group_num = self.sync_opts.group_num.unwrap_or(DEFAULT_GROUP_NUM);
+                get_confirmed_timestamp(self.db_pool.clone(), confirmations, group_num).await?
+            }
+            _ => None,
+        };
+        let now = chrono::Utc::now().timestamp_millis();
+        Ok(finality.cutoff(now, self.reorg_timeout(), confirmed_ts))
+    }
+
+    fn reorg_timeout(&self) -> i64 {
+        self.sync_opts.reorg_timeout.unwrap_or(REORG_TIMEOUT)
+    }
+
+    fn sync_duration(&self) -> Duration {
+        Duration::from_secs(self.sync_opts.sync_duration.unwrap_or(1) as u64)
+    }
+
+    /// Backfills history from `current_ts` up to `to_ts` by fetching up to
+    /// `backfill_concurrency` windows of `step` ms concurrently. Windows are sized when they are
+    /// scheduled, so an adaptive step only affects windows not yet requested.
+    ///
+    /// Windows are handed to the processors in timestamp order, and a checkpoint only moves
+    /// past a window once it and every window before it were processed, so a failure or a crash
+    /// never leaves holes. The blocks are older than the reorg interval, so no reorg handling
+    /// is needed. On shutdown, windows fetched ahead are dropped and `current_ts` is left at the
+    /// first unprocessed one.
+    async fn backfill(
+        &self,
+        tasks: &mut [ProcessorTask],
+        current_ts: &mut i64,
+        to_ts: i64,
+        step: &mut AdaptiveStep,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let processor_names = processor_names(&self.processor_configs);
+        let concurrency = self.sync_opts.backfill_concurrency.unwrap_or(1).max(1);
+        tracing::info!(
+            processors = processor_names,
+            from_ts = *current_ts,
+            to_ts = to_ts,
+            concurrency = concurrency,
+            "Backfilling blocks"
+        );
+
+        let step = Mutex::new(step);
+        let windows = split_windows(*current_ts, to_ts, || step.lock().unwrap().current());
+        let mut windows = futures::stream::iter(windows)
+            .map(|(from, to)| async move {
+                self.client.get_blocks_and_events(from, to).await.map(|blocks| (from, to, blocks))
+            })
+            .buffered(concurrency);
+
+        while let Some(window) = windows.next().await {
+            if shutdown.is_cancelled() {
+                break;
+            }
+            let (from, to, blocks) = window?;
+            let count = block_count(&blocks.blocks_and_events);
+            tracing::info!(
+                processors = processor_names,
+                from_ts = from,
+                to_ts = to,
+                block_count = count,
+                "Backfilled window"
+            );
+            self.process_window(tasks, from, to, blocks.blocks_and_events).await?;
+            *current_ts = to + 1;
+            step.lock().unwrap().record(count);
+        }
+        Ok(())
+    }
+
+    /// Follows the chain tip through websocket block notifications.
+    ///
+    /// After subscribing, the window between `current_ts` and now is polled once so that no
+    /// block is missed while the subscription was being set up. Each notified block is then
+    /// fetched with its events and processed on its own, and the checkpoints advanced to its
+    /// timestamp. Blocks older than the polling cursor were already covered by polling and are
+    /// skipped.
+    ///
+    /// Processors requiring finality keep catching up every `sync_duration` meanwhile.
+    ///
+    /// Only returns on shutdown, or when the socket dies or a block fails to be fetched or
+    /// processed by any processor, leaving `current_ts` at the point polling should resume from.
+    async fn live_tail(
+        &self,
+        tasks: &mut [ProcessorTask],
+        ws_url: &str,
+        current_ts: &mut i64,
+        step: i64,
+        shutdown: &CancellationToken,
+    ) -> Result<()> {
+        let processor_names = processor_names(&self.processor_configs);
+        let (mut ws, _) = WsClient::connect_async(ws_url).await?;
+        let mut notified = ws.blocks().context("Block stream already taken")?;
+        ws.subscribe_blocks().await?;
+        tracing::info!(processors = processor_names, "Switched to live tail");
+
+        let polled_until = *current_ts;
+        let mut seen = RecentHashes::new(LIVE_TAIL_DEDUP_CAPACITY);
+
+        // Catch up with the blocks mined before the subscription was active
+        let to_ts = chrono::Utc::now().timestamp_millis();
+        let blocks = self.client.get_blocks_and_events(*current_ts, to_ts).await?;
+        for bes in blocks.blocks_and_events.iter() {
+            for be in bes {
+                seen.insert(&be.block.hash);
+            }
+        }
+        self.process_window(tasks, *current_ts, to_ts, blocks.blocks_and_events).await?;
+        *current_ts = to_ts + 1;
+        if tasks.iter().any(|task| task.follows_tip() && task.next_ts < *current_ts) {
+            let _ = ws.close().await;
+            return Err(anyhow!("A processor fell behind"));
+        }
+
+        let trailing = tasks.iter().any(|task| !task.follows_tip());
+        let mut catch_up_ticker = tokio::time::interval(self.sync_duration());
+        loop {
+            let block = tokio::select! {
+                block = notified.next() => block,
+                _ = catch_up_ticker.tick(), if trailing => {
+                    self.catch_up(tasks, *current_ts, step, shutdown).await;
                     continue;
                 }
+                _ = shutdown.cancelled() => {
+                    let _ = ws.close().await;
+                    return Ok(());
+                }
+            };
+            let Some(block) = block else {
+                break;
+            };
+            let block = block?;
+            if block.timestamp < polled_until || !seen.insert(&block.hash) {
+                continue;
             }
 
-            tracing::info!(processor_name = processor_name, "Sleeping for {:?}", sync_duration);
-            sleep(sync_duration).await;
+            let block_and_events = self.client.get_block_and_events_by_hash(&block.hash).await?;
+            let ts = block_and_events.block.timestamp;
+            tracing::info!(
+                processors = processor_names,
+                block_hash = block.hash,
+                timestamp = ts,
+                "Processing notified block"
+            );
+            let failed = self.process_window(tasks, ts, ts, vec![vec![block_and_events]]).await?;
+            if failed > 0 {
+                let _ = ws.close().await;
+                return Err(anyhow!("A processor fell behind"));
+            }
+            *current_ts = (*current_ts).max(ts + 1);
         }
+
+        let _ = ws.close().await;
+        Err(anyhow!("Websocket block stream ended"))
     }
 
     // For the normal processor build we just use standard Diesel with the postgres
     // feature enabled (which uses libpq under the hood, hence why we named the feature
     // this way).
     #[cfg(feature = "libpq")]
-    async fn run_migrations(&self) {
+    async fn run_migrations(&self) -> Result<()> {
         use diesel::{pg::PgConnection, Connection};
 
         use crate::db::run_pending_migrations;
 
         tracing::info!("Running migrations: {:?}", self.db_url);
-        let mut conn = PgConnection::establish(&self.db_url).expect("migrations failed!");
-        run_pending_migrations(&mut conn);
+        let mut conn = PgConnection::establish(&self.db_url)
+            .context("Failed to connect to the database to run migrations")?;
+        run_pending_migrations(&mut conn).map_err(|err| anyhow!(err))?;
+        Ok(())
     }
 
     // Inserts a block into the database and handles chain reorganization if necessary.
@@ -188,48 +995,74 @@ impl Worker {
     /// * `block` - The block model to be inserted
     ///
     /// # Returns
-    /// * `Result<()>` - Success or error result
+    /// * `Result<MainChainUpdate>` - The blocks that left or joined the main chain
     ///
     /// # Flow
     /// 1. Checks if block has a parent
     /// 2. For blocks with parent:
-    ///    - Verifies parent exists and handles chain reorganization if needed
+    ///    - Downloads the parent and its missing ancestors if it isn't stored yet
+    ///    - Handles chain reorganization if needed
     ///    - Inserts the block and updates main chain status
     /// 3. For genesis blocks (no parent):
     ///    - Validates height is 0
     ///    - Inserts directly
-    async fn insert(&self, db: Arc<DbPool>, block: BlockModel) -> Result<()> {
-        match block.parent(None) {
+    async fn insert(&self, db: Arc<DbPool>, block: BlockModel) -> Result<MainChainUpdate> {
+        match block.parent(self.sync_opts.group_num) {
             Some(parent) => {
-                let parent_info = get_block_by_hash(db.clone(), &parent).await?;
+                let parent_info = match get_block_by_hash(db.clone(), &parent).await? {
+                    Some(parent_info) => Some(parent_info),
+                    None => {
+                        self.fetch_missing_ancestors(db.clone(), parent.clone()).await?;
+                        get_block_by_hash(db.clone(), &parent).await?
+                    }
+                };
                 match parent_info {
-                    None => unimplemented!(),
+                    None => Err(anyhow!(
+                        "Parent block {} of {} could not be fetched",
+                        parent,
+                        block.hash
+                    )),
                     Some(parent_info) => {
+                        let mut update = MainChainUpdate::default();
                         if !parent_info.main_chain {
-                            assert_eq!(parent_info.chain_from, block.chain_from);
-                            assert_eq!(parent_info.chain_to, block.chain_to);
-                            update_main_chain(
+                            if (parent_info.chain_from, parent_info.chain_to)
+                                != (block.chain_from, block.chain_to)
+                            {
+                                return Err(anyhow!(
+                                    "Parent block {} of {} is on chain {}->{}, expected {}->{}",
+                                    parent_info.hash,
+                                    block.hash,
+                                    parent_info.chain_from,
+                                    parent_info.chain_to,
+                                    block.chain_from,
+                                    block.chain_to
+                                ));
+                            }
+                            let parent_update = update_main_chain(
                                 db.clone(),
                                 parent_info.hash,
                                 block.chain_from,
                                 block.chain_to,
-                                None,
+                                self.sync_opts.group_num,
                             )
                             .await?;
+                            update.merge(parent_update);
                         }
 
                         // After handle parent, we can insert the block
                         // TODO: handle uncles
                         insert_blocks_to_db(db.clone(), vec![block.clone()]).await?;
-                        update_main_chain(
-                            db,
-                            block.clone().hash,
-                            block.clone().chain_from,
-                            block.clone().chain_to,
-                            None,
-                        )
-                        .await?;
-                        Ok(())
+                        update.merge(
+                            update_main_chain(
+                                db,
+                                block.clone().hash,
+                                block.clone().chain_from,
+                                block.clone().chain_to,
+                                self.sync_opts.group_num,
+                            )
+                            .await?,
+                        );
+                        Ok(update)
                     }
                 }
             }
@@ -237,14 +1070,73 @@ impl Worker {
                 if block.height != 0 {
                     tracing::error!("Block with no parent and height > 0: {:?}", block);
                 }
-                Ok(())
+                Ok(MainChainUpdate::default())
             }
         }
     }
+
+    /// Downloads the ancestors missing from the database, walking down the chain from `hash`
+    /// until a known main chain block, the genesis block, or `max_parent_fetch_depth`
+    /// ancestors, and inserts them so that main chain marking can go through them.
+    ///
+    /// # Arguments
+    /// * `db` - Thread-safe reference to the database connection pool
+    /// * `hash` - The hash of the first missing block
+    ///
+    /// # Returns
+    /// * `Result<usize>` - The number of blocks downloaded
+    async fn fetch_missing_ancestors(&self, db: Arc<DbPool>, hash: BlockHash) -> Result<usize> {
+        let max_depth =
+            self.sync_opts.max_parent_fetch_depth.unwrap_or(DEFAULT_MAX_PARENT_FETCH_DEPTH);
+        let mut missing = Vec::new();
+        let mut depth = 0;
+        let mut current = Some(hash);
+
+        while let Some(hash) = current.take() {
+            if depth >= max_depth {
+                tracing::warn!(
+                    block_hash = hash,
+                    max_depth = max_depth,
+                    "Reached the parent fetch depth limit, older ancestors are left missing"
+                );
+                break;
+            }
+            depth += 1;
+
+            match get_block_by_hash(db.clone(), &hash).await? {
+                Some(known) if known.main_chain => break,
+                // Known side chain block, its ancestors may still be missing
+                Some(known) => current = known.parent(self.sync_opts.group_num),
+                None => {
+                    let entry = self.client.get_block(&hash).await?;
+                    current = (entry.height > 0).then(|| entry.parent.clone());
+                    missing.push(convert_block_entry_to_block_model(entry));
+                }
+            }
+        }
+
+        let count = missing.len();
+        if count > 0 {
+            tracing::info!(count = count, "Downloaded missing ancestors");
+            // Oldest first, like the blocks of a sync window
+            missing.reverse();
+            insert_blocks_to_db(db, missing).await?;
+        }
+        Ok(count)
+    }
 }
 
 /// Build a processor based on the configuration.
-pub fn build_processor(config: &ProcessorConfig, db_pool: Arc<DbPool>) -> Processor {
+///
+/// # Arguments
+///
+/// * `group_num` - The number of groups of the network, used by the processors calling contracts.
+pub fn build_processor(
+    config: &ProcessorConfig,
+    db_pool: Arc<DbPool>,
+    client: Arc<Client>,
+    group_num: i64,
+) -> Processor {
     match config {
         ProcessorConfig::DefaultProcessor => {
             Processor::DefaultProcessor(DefaultProcessor::new(db_pool))
@@ -257,9 +1149,151 @@ pub fn build_processor(config: &ProcessorConfig, db_pool: Arc<DbPool>) -> Proces
                 contract_address.clone(),
             ))
         }
+        ProcessorConfig::TransactionProcessor => {
+            Processor::TransactionProcessor(TransactionProcessor::new(db_pool))
+        }
+        ProcessorConfig::UtxoProcessor => Processor::UtxoProcessor(UtxoProcessor::new(db_pool)),
+        ProcessorConfig::TokenTransferProcessor => {
+            Processor::TokenTransferProcessor(TokenTransferProcessor::new(db_pool))
+        }
+        ProcessorConfig::ContractProcessor { snapshot_states } => {
+            Processor::ContractProcessor(ContractProcessor::new(db_pool, client, *snapshot_states))
+        }
+        ProcessorConfig::TokenRegistryProcessor => Processor::TokenRegistryProcessor(
+            TokenRegistryProcessor::new(db_pool, client, group_num),
+        ),
+        ProcessorConfig::NftProcessor => {
+            Processor::NftProcessor(NftProcessor::new(db_pool, client, group_num))
+        }
+        ProcessorConfig::DexProcessor(factory_address) => {
+            Processor::DexProcessor(DexProcessor::new(db_pool, factory_address.clone()))
+        }
+    }
+}
+
+/// Returns the names of the configured processors, for logging.
+fn processor_names(configs: &[ProcessorConfig]) -> String {
+    configs.iter().map(ProcessorConfig::name).collect::<Vec<_>>().join(",")
+}
+
+/// Returns the highest main chain block of each chain in a window.
+fn chain_tips(blocks: &[Vec<BlockAndEvents>]) -> HashMap<ChainIndex, &BlockEntry> {
+    let mut tips: HashMap<ChainIndex, &BlockEntry> = HashMap::new();
+    for be in blocks.iter().flatten().filter(|be| be.block.main_chain) {
+        let chain = (be.block.chain_from, be.block.chain_to);
+        if tips.get(&chain).is_none_or(|tip| tip.height < be.block.height) {
+            tips.insert(chain, &be.block);
+        }
+    }
+    tips
+}
+
+/// Returns the chain checkpoints of a processor at the given blocks.
+fn checkpoints_of(
+    processor_name: &str,
+    blocks: &HashMap<ChainIndex, &BlockEntry>,
+) -> Vec<ProcessorChainStatusModel> {
+    blocks
+        .values()
+        .map(|block| ProcessorChainStatusModel {
+            processor: processor_name.to_string(),
+            chain_from: block.chain_from,
+            chain_to: block.chain_to,
+            block_hash: block.hash.clone(),
+            height: block.height,
+            timestamp: block.timestamp,
+        })
+        .collect()
+}
+
+/// Returns the blocks missing between the checkpoint `heights` of each chain and the lowest
+/// block of that chain in a window.
+fn find_gaps(heights: &HashMap<ChainIndex, i64>, blocks: &[Vec<BlockAndEvents>]) -> Vec<ChainGap> {
+    let mut lowest: HashMap<ChainIndex, &BlockEntry> = HashMap::new();
+    for be in blocks.iter().flatten() {
+        let chain = (be.block.chain_from, be.block.chain_to);
+        if lowest.get(&chain).is_none_or(|block| be.block.height < block.height) {
+            lowest.insert(chain, &be.block);
+        }
+    }
+
+    let mut gaps: Vec<ChainGap> = lowest
+        .into_iter()
+        .filter_map(|(chain, block)| {
+            let height = *heights.get(&chain)?;
+            (block.height > height + 1).then(|| ChainGap {
+                chain,
+                from_height: height + 1,
+                to_height: block.height - 1,
+                top_hash: block.parent.clone(),
+            })
+        })
+        .collect();
+    gaps.sort_by_key(|gap| gap.chain);
+    gaps
+}
+
+/// Returns the checkpoints to roll back to the fork point of their chain, for the chains whose
+/// checkpoint is past it.
+fn rollback_checkpoints(
+    chains: &HashMap<ChainIndex, ProcessorChainStatusModel>,
+    forks: &HashMap<ChainIndex, BlockModel>,
+) -> Vec<ProcessorChainStatusModel> {
+    forks
+        .iter()
+        .filter_map(|(chain, fork)| {
+            let checkpoint = chains.get(chain)?;
+            (checkpoint.height > fork.height).then(|| ProcessorChainStatusModel {
+                processor: checkpoint.processor.clone(),
+                chain_from: chain.0,
+                chain_to: chain.1,
+                block_hash: fork.hash.clone(),
+                height: fork.height,
+                timestamp: fork.timestamp.and_utc().timestamp_millis(),
+            })
+        })
+        .collect()
+}
+
+/// Sleeps for `duration`, waking up early if shutdown is requested.
+async fn sleep_or_shutdown(duration: Duration, shutdown: &CancellationToken) {
+    tokio::select! {
+        _ = sleep(duration) => {}
+        _ = shutdown.cancelled() => {}
     }
 }
 
+/// Returns the timestamp to start syncing from.
+///
+/// A bounded re-index (with `end_ts`) starts at `start_ts` even if it is behind the checkpoint.
+/// Otherwise the worker resumes `back_step` ms behind the checkpoint, but never before `start_ts`.
+fn initial_timestamp(sync_opts: &SyncOptions, last_ts: i64) -> i64 {
+    let resume_ts = (last_ts - sync_opts.back_step.unwrap_or(0)).max(0);
+    match (sync_opts.start_ts, sync_opts.end_ts) {
+        (Some(start_ts), Some(_)) => start_ts,
+        (start_ts, _) => start_ts.unwrap_or(0).max(resume_ts),
+    }
+}
+
+/// Splits `[from_ts, to_ts]` into consecutive, non-overlapping windows of at most `step + 1` ms,
+/// matching the inclusive windows used by the polling loop. The step is read as each window is
+/// generated.
+fn split_windows(
+    from_ts: i64,
+    to_ts: i64,
+    mut step: impl FnMut() -> i64,
+) -> impl Iterator<Item = (i64, i64)> {
+    let mut next = from_ts;
+    std::iter::from_fn(move || {
+        if next > to_ts {
+            return None;
+        }
+        let window = (next, (next + step()).min(to_ts));
+        next = window.1 + 1;
+        Some(window)
+    })
+}
+
 async fn get_last_timestamp(db_pool: &Arc<DbPool>, processor_name: &str) -> Result<i64> {
     tracing::info!(processor = processor_name, "Getting last timestamp");
     let mut conn = db_pool.get().await?;
@@ -290,9 +1324,268 @@ async fn update_last_timestamp(
         ))
         .on_conflict(processor_status::processor)
         .do_update()
-        .set(processor_status::last_timestamp.eq(last_timestamp))
+        // Never move the checkpoint backwards, e.g. when re-indexing an older range
+        .set(processor_status::last_timestamp.eq(diesel::dsl::sql::<BigInt>(
+            "GREATEST(processor_status.last_timestamp, excluded.last_timestamp)",
+        )))
         .execute(&mut conn)
         .await
         .map(|_| ())
         .map_err(anyhow::Error::new)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sleep_or_shutdown() {
+        let shutdown = CancellationToken::new();
+        shutdown.cancel();
+        let started = std::time::Instant::now();
+        sleep_or_shutdown(Duration::from_secs(60), &shutdown).await;
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_processor_names() {
+        let configs = vec![
+            ProcessorConfig::BlockProcessor,
+            ProcessorConfig::EventProcessor,
+            ProcessorConfig::LendingContractProcessor("address".into()),
+        ];
+        assert_eq!(
+            processor_names(&configs),
+            "block_processor,event_processor,lending_contract_processor"
+        );
+    }
+
+    fn block(chain: ChainIndex, height: i64, hash: &str, parent: &str) -> BlockAndEvents {
+        BlockAndEvents {
+            block: BlockEntry {
+                hash: hash.to_string(),
+                timestamp: height * 1000,
+                chain_from: chain.0,
+                chain_to: chain.1,
+                height,
+                deps: vec![],
+                transactions: vec![],
+                nonce: String::new(),
+                version: 0,
+                dep_state_hash: String::new(),
+                txs_hash: String::new(),
+                target: String::new(),
+                parent: parent.to_string(),
+                main_chain: true,
+                ghost_uncles: vec![],
+            },
+            events: vec![],
+        }
+    }
+
+    fn checkpoint(chain: ChainIndex, height: i64, hash: &str) -> ProcessorChainStatusModel {
+        ProcessorChainStatusModel {
+            processor: "event_processor".to_string(),
+            chain_from: chain.0,
+            chain_to: chain.1,
+            block_hash: hash.to_string(),
+            height,
+            timestamp: height * 1000,
+        }
+    }
+
+    #[test]
+    fn test_chain_tips() {
+        let mut orphan = block((0, 1), 12, "orphan", "b11");
+        orphan.block.main_chain = false;
+        let blocks = vec![
+            vec![block((0, 1), 10, "b10", "b9"), block((0, 1), 11, "b11", "b10")],
+            vec![orphan, block((2, 3), 5, "c5", "c4")],
+        ];
+
+        let tips = chain_tips(&blocks);
+        assert_eq!(tips.len(), 2);
+        assert_eq!(tips[&(0, 1)].hash, "b11");
+        assert_eq!(tips[&(2, 3)].hash, "c5");
+
+        let mut checkpoints = checkpoints_of("event_processor", &tips);
+        checkpoints.sort_by_key(|checkpoint| checkpoint.chain_from);
+        assert_eq!(checkpoints, vec![checkpoint((0, 1), 11, "b11"), checkpoint((2, 3), 5, "c5")]);
+    }
+
+    #[test]
+    fn test_find_gaps() {
+        let blocks = vec![vec![
+            block((0, 1), 14, "b14", "b13"),
+            block((0, 1), 15, "b15", "b14"),
+            block((1, 1), 8, "c8", "c7"),
+            block((3, 3), 2, "d2", "d1"),
+        ]];
+        let heights = HashMap::from([((0, 1), 10), ((1, 1), 7), ((2, 2), 3)]);
+
+        // Heights 11 to 13 are missing on chain 0 -> 1, chain 1 -> 1 is contiguous, and chain
+        // 3 -> 3 has no checkpoint yet
+        assert_eq!(
+            find_gaps(&heights, &blocks),
+            vec![ChainGap {
+                chain: (0, 1),
+                from_height: 11,
+                to_height: 13,
+                top_hash: "b13".to_string(),
+            }]
+        );
+        assert!(find_gaps(&HashMap::new(), &blocks).is_empty());
+    }
+
+    #[test]
+    fn test_rollback_checkpoints() {
+        let chains = HashMap::from([
+            ((0, 1), checkpoint((0, 1), 12, "b12")),
+            ((2, 3), checkpoint((2, 3), 5, "c5")),
+        ]);
+        let fork = |height: i64, hash: &str| BlockModel {
+            hash: hash.to_string(),
+            timestamp: crate::utils::timestamp_millis_to_naive_datetime(height * 1000),
+            chain_from: 0,
+            chain_to: 1,
+            height,
+            deps: vec![],
+            nonce: String::new(),
+            version: String::new(),
+            dep_state_hash: String::new(),
+            txs_hash: String::new(),
+            tx_number: 0,
+            target: String::new(),
+            main_chain: true,
+            ghost_uncles: serde_json::Value::Null,
+        };
+
+        // The checkpoint is past the fork point, roll it back
+        let forks = HashMap::from([((0, 1), fork(10, "b10"))]);
+        assert_eq!(rollback_checkpoints(&chains, &forks), vec![checkpoint((0, 1), 10, "b10")]);
+
+        // The checkpoint is behind the fork point, or there is none
+        let forks = HashMap::from([((0, 1), fork(12, "b12")), ((1, 1), fork(3, "x3"))]);
+        assert!(rollback_checkpoints(&chains, &forks).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_record_checkpoints() {
+        let mut task = ProcessorTask {
+            processor: Processor::DefaultProcessor(DefaultProcessor::new(Arc::new(
+                DbPool::builder().build_unchecked(
+                    diesel_async::pooled_connection::AsyncDieselConnectionManager::new(
+                        "postgres://localhost/unused",
+                    ),
+                ),
+            ))),
+            next_ts: 0,
+            chains: HashMap::from([((0, 1), checkpoint((0, 1), 12, "b12"))]),
+            finality: Finality::Latest,
+        };
+
+        // Tips never move a checkpoint backwards
+        task.record_checkpoints(
+            vec![],
+            vec![checkpoint((0, 1), 11, "b11"), checkpoint((2, 3), 5, "c5")],
+        );
+        assert_eq!(task.chains[&(0, 1)].height, 12);
+        assert_eq!(task.chains[&(2, 3)].height, 5);
+
+        // Rollbacks do, then tips apply on top of them
+        task.record_checkpoints(
+            vec![checkpoint((0, 1), 10, "b10")],
+            vec![checkpoint((0, 1), 11, "b11'")],
+        );
+        assert_eq!(task.chains[&(0, 1)], checkpoint((0, 1), 11, "b11'"));
+        assert_eq!(task.chain_heights(), HashMap::from([((0, 1), 11), ((2, 3), 5)]));
+    }
+
+    #[test]
+    fn test_split_windows() {
+        let windows: Vec<_> = split_windows(0, 2500, || 1000).collect();
+        assert_eq!(windows, vec![(0, 1000), (1001, 2001), (2002, 2500)]);
+
+        let windows: Vec<_> = split_windows(10, 10, || 1000).collect();
+        assert_eq!(windows, vec![(10, 10)]);
+
+        assert_eq!(split_windows(11, 10, || 1000).count(), 0);
+
+        let mut step = 100;
+        let windows: Vec<_> = split_windows(0, 1000, || {
+            step *= 2;
+            step
+        })
+        .collect();
+        assert_eq!(windows, vec![(0, 200), (201, 601), (602, 1000)]);
+    }
+
+    #[test]
+    fn test_finality_cutoff() {
+        let (now, reorg_timeout) = (1_000_000, 100_000);
+        assert_eq!(Finality::Latest.cutoff(now, reorg_timeout, None), i64::MAX);
+        assert_eq!(Finality::Window(60_000).cutoff(now, reorg_timeout, None), 940_000);
+        // Confirmed blocks are final, and so is everything out of the reorg interval
+        let confirmations = Finality::Confirmations(10);
+        assert_eq!(confirmations.cutoff(now, reorg_timeout, Some(980_000)), 980_000);
+        assert_eq!(confirmations.cutoff(now, reorg_timeout, Some(800_000)), 900_000);
+        assert_eq!(confirmations.cutoff(now, reorg_timeout, None), 900_000);
+    }
+
+    #[test]
+    fn test_initial_timestamp() {
+        // Fresh start
+        let sync_opts = SyncOptions { start_ts: Some(1000), ..Default::default() };
+        assert_eq!(initial_timestamp(&sync_opts, 0), 1000);
+
+        // Resume from the checkpoint, rewinding by back_step
+        assert_eq!(initial_timestamp(&sync_opts, 5000), 5000);
+        let sync_opts =
+            SyncOptions { start_ts: Some(1000), back_step: Some(2000), ..Default::default() };
+        assert_eq!(initial_timestamp(&sync_opts, 5000), 3000);
+        // ... but never before start_ts
+        assert_eq!(initial_timestamp(&sync_opts, 2000), 1000);
+        let sync_opts = SyncOptions { back_step: Some(2000), ..Default::default() };
+        assert_eq!(initial_timestamp(&sync_opts, 1000), 0);
+
+        // Re-index a fixed range behind the checkpoint
+        let sync_opts =
+            SyncOptions { start_ts: Some(1000), end_ts: Some(2000), ..Default::default() };
+        assert_eq!(initial_timestamp(&sync_opts, 5000), 1000);
+    }
+
+    #[test]
+    fn test_adaptive_step() {
+        let sync_opts = SyncOptions {
+            step: Some(1000),
+            min_step: Some(250),
+            max_step: Some(4000),
+            target_block_count: Some(100),
+            ..Default::default()
+        };
+        let mut step = AdaptiveStep::new(&sync_opts);
+
+        // Quiet chain: grow up to the maximum
+        assert_eq!(step.record(0), 2000);
+        assert_eq!(step.record(10), 4000);
+        assert_eq!(step.record(10), 4000);
+
+        // Within the target: keep the window
+        assert_eq!(step.record(80), 4000);
+
+        // Busy chain: shrink down to the minimum
+        assert_eq!(step.record(500), 2000);
+        assert_eq!(step.record(500), 1000);
+        assert_eq!(step.record(500), 500);
+        assert_eq!(step.record(500), 250);
+        assert_eq!(step.record(500), 250);
+    }
+
+    #[test]
+    fn test_fixed_step() {
+        let mut step = AdaptiveStep::new(&SyncOptions { step: Some(1000), ..Default::default() });
+        assert!(!step.is_adaptive());
+        assert_eq!(step.record(0), 1000);
+        assert_eq!(step.record(100_000), 1000);
+    }
+}
@@ -1,105 +1,479 @@
-use futures_util::SinkExt;
-use std::fmt;
-use tokio::io::{AsyncRead, AsyncWrite};
-use tokio::net::TcpStream;
+use crate::{
+    client::{Client, ClientError},
+    types::BlockEntry,
+};
+use futures::{channel::mpsc as stream_channel, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
 use tokio_tungstenite::{
     connect_async,
-    tungstenite::{handshake::client::Response, protocol::Message, Error},
-    MaybeTlsStream, WebSocketStream,
+    tungstenite::{self, handshake::client::Response, protocol::Message},
+    WebSocketStream,
 };
 
 // Example usage:
 // #[tokio::main]
 // async fn main() {
-//     let (mut conn, _) = WsClient::connect_async(URL)
+//     let (mut client, _) = WsClient::connect_async(URL)
 //         .await
 //         .expect("Failed to connect");
-//     conn.subscribe_blocks().await;
-//     let timer = tokio::time::Instant::now();
-//     let duration = Duration::new(10, 0);
-//     while let Some(message) = conn.as_mut().next().await {
-//         if timer.elapsed() >= duration {
-//             break;
-//         }
-//         match message {
-//             Ok(message) => {
-//                 let data = message.into_data();
-//                 let string_data = String::from_utf8(data).expect("Found invalid UTF-8 chars");
-//                 tracing::info!("Received: {}", string_data);
+//     let mut blocks = client.blocks().expect("Block stream already taken");
+//     let subscription = client.subscribe_blocks().await.expect("Failed to subscribe");
+//     while let Some(block) = blocks.next().await {
+//         match block {
+//             Ok(block) => tracing::info!("Received block: {}", block.hash),
+//             Err(err) => {
+//                 tracing::error!("Websocket error: {}", err);
+//                 break;
 //             }
-//             Err(_) => break,
 //         }
 //     }
-//     conn.close().await.expect("Failed to disconnect");
+//     client.unsubscribe(&subscription).await.expect("Failed to unsubscribe");
+//     client.close().await.expect("Failed to disconnect");
 // }
 
-pub struct WsClient;
+/// Method of the notifications pushed by nodes for every new block.
+pub const BLOCK_NOTIFY_METHOD: &str = "block_notify";
+/// Method of the notifications pushed for a subscription created through `subscribe`.
+pub const SUBSCRIPTION_METHOD: &str = "subscription";
+
+pub type SubscriptionId = String;
+
+/// Stream of the blocks notified by the node.
+pub type BlockStream = stream_channel::UnboundedReceiver<Result<BlockEntry, WsError>>;
+
+/// Errors returned by the websocket client.
+#[derive(Debug, thiserror::Error)]
+pub enum WsError {
+    #[error("Websocket error: {0}")]
+    Socket(#[from] tungstenite::Error),
+    #[error("Failed to encode or decode message: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("JSON-RPC error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("Websocket connection closed")]
+    Closed,
+    #[error("Failed to backfill missed blocks: {0}")]
+    Backfill(#[from] ClientError),
+}
+
+#[derive(Serialize, Debug)]
+struct Request<'a> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: Vec<Value>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// Messages received from the node: either the response to a request, or a notification.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Incoming {
+    Response {
+        id: u64,
+        #[serde(default)]
+        result: Option<Value>,
+        #[serde(default)]
+        error: Option<RpcError>,
+    },
+    Notification {
+        method: String,
+        params: Value,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+struct SubscriptionNotification {
+    #[allow(dead_code)]
+    subscription: SubscriptionId,
+    result: Value,
+}
+
+/// Decodes the block carried by a notification, or returns `None` for other notifications.
+fn decode_block_notification(method: &str, params: Value) -> Option<Result<BlockEntry, WsError>> {
+    let block = match method {
+        BLOCK_NOTIFY_METHOD => params,
+        SUBSCRIPTION_METHOD => match serde_json::from_value::<SubscriptionNotification>(params) {
+            Ok(notification) => notification.result,
+            Err(err) => return Some(Err(err.into())),
+        },
+        _ => return None,
+    };
+    Some(serde_json::from_value(block).map_err(WsError::from))
+}
+
+enum Command {
+    Request { id: u64, text: String, response: oneshot::Sender<Result<Value, WsError>> },
+    Close { done: oneshot::Sender<Result<(), WsError>> },
+}
+
+/// Typed JSON-RPC client for the node's websocket API.
+///
+/// The socket is owned by a background task which correlates responses with requests by `id`
+/// and forwards block notifications to the stream returned by [`WsClient::blocks`].
+pub struct WsClient {
+    commands: mpsc::UnboundedSender<Command>,
+    next_id: AtomicU64,
+    blocks: Option<BlockStream>,
+    task: JoinHandle<()>,
+}
 
 impl WsClient {
-    pub async fn connect_async(
-        url: &str,
-    ) -> Result<(ConnectionState<MaybeTlsStream<TcpStream>>, Response), Error> {
+    pub async fn connect_async(url: &str) -> Result<(Self, Response), WsError> {
         let (socket, response) = connect_async(url).await?;
-        Ok((ConnectionState::new(socket), response))
+        Ok((Self::new(socket), response))
+    }
+
+    /// Creates a client over an already established websocket connection.
+    pub fn new<T>(socket: WebSocketStream<T>) -> Self
+    where
+        T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (commands, commands_rx) = mpsc::unbounded_channel();
+        let (blocks_tx, blocks) = stream_channel::unbounded();
+        let task = tokio::spawn(run_connection(socket, commands_rx, blocks_tx));
+        Self { commands, next_id: AtomicU64::new(0), blocks: Some(blocks), task }
+    }
+
+    /// Returns the stream of notified blocks, or `None` if it was already taken.
+    ///
+    /// The stream ends when the connection is closed, after yielding the error that closed it.
+    pub fn blocks(&mut self) -> Option<BlockStream> {
+        self.blocks.take()
+    }
+
+    /// Sends a JSON-RPC request and waits for its result.
+    pub async fn request(&self, method: &str, params: Vec<Value>) -> Result<Value, WsError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let text = serde_json::to_string(&Request { jsonrpc: "2.0", id, method, params })?;
+        let (response, response_rx) = oneshot::channel();
+        self.commands.send(Command::Request { id, text, response }).map_err(|_| WsError::Closed)?;
+        response_rx.await.map_err(|_| WsError::Closed)?
+    }
+
+    /// Subscribes to new blocks, which are then yielded by the stream returned by
+    /// [`WsClient::blocks`].
+    pub async fn subscribe_blocks(&self) -> Result<SubscriptionId, WsError> {
+        let result = self.request("subscribe", vec![Value::from("block")]).await?;
+        Ok(match result {
+            Value::String(id) => id,
+            other => other.to_string(),
+        })
+    }
+
+    /// Cancels a subscription, returning whether the node knew about it.
+    pub async fn unsubscribe(&self, subscription: &SubscriptionId) -> Result<bool, WsError> {
+        let result = self.request("unsubscribe", vec![Value::from(subscription.as_str())]).await?;
+        Ok(result.as_bool().unwrap_or(true))
+    }
+
+    /// Closes the connection and waits for the background task to finish.
+    pub async fn close(self) -> Result<(), WsError> {
+        let (done, done_rx) = oneshot::channel();
+        if self.commands.send(Command::Close { done }).is_err() {
+            // The connection is already gone
+            return Ok(());
+        }
+        let result = done_rx.await.unwrap_or(Ok(()));
+        let _ = self.task.await;
+        result
     }
 }
 
-pub struct ConnectionState<T> {
-    socket: WebSocketStream<T>,
-    id: u64,
+/// Owns the socket: writes requests, routes responses to their caller and notifications to the
+/// block stream, until the connection closes or the client asks to close it.
+async fn run_connection<T>(
+    mut socket: WebSocketStream<T>,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    blocks: stream_channel::UnboundedSender<Result<BlockEntry, WsError>>,
+) where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut pending: HashMap<u64, oneshot::Sender<Result<Value, WsError>>> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(Command::Request { id, text, response }) => {
+                    match socket.send(Message::Text(text.into())).await {
+                        Ok(()) => {
+                            pending.insert(id, response);
+                        }
+                        Err(err) => {
+                            let _ = response.send(Err(err.into()));
+                        }
+                    }
+                }
+                Some(Command::Close { done }) => {
+                    let _ = done.send(socket.close(None).await.map_err(WsError::from));
+                    break;
+                }
+                // The client was dropped
+                None => {
+                    let _ = socket.close(None).await;
+                    break;
+                }
+            },
+            message = socket.next() => match message {
+                Some(Ok(Message::Text(text))) => handle_text(&text, &mut pending, &blocks),
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(err)) => {
+                    let _ = blocks.unbounded_send(Err(err.into()));
+                    break;
+                }
+            }
+        }
+    }
+
+    for (_, response) in pending.drain() {
+        let _ = response.send(Err(WsError::Closed));
+    }
 }
 
-impl<T: AsyncRead + AsyncWrite + Unpin> ConnectionState<T> {
-    pub fn new(socket: WebSocketStream<T>) -> Self {
-        Self { socket, id: 0 }
+fn handle_text(
+    text: &str,
+    pending: &mut HashMap<u64, oneshot::Sender<Result<Value, WsError>>>,
+    blocks: &stream_channel::UnboundedSender<Result<BlockEntry, WsError>>,
+) {
+    match serde_json::from_str::<Incoming>(text) {
+        Ok(Incoming::Response { id, result, error }) => {
+            let Some(response) = pending.remove(&id) else {
+                tracing::warn!(id = id, "Received response for unknown request");
+                return;
+            };
+            let result = match error {
+                Some(error) => Err(WsError::Rpc { code: error.code, message: error.message }),
+                None => Ok(result.unwrap_or(Value::Null)),
+            };
+            let _ = response.send(result);
+        }
+        Ok(Incoming::Notification { method, params }) => {
+            if let Some(block) = decode_block_notification(&method, params) {
+                let _ = blocks.unbounded_send(block);
+            }
+        }
+        Err(err) => {
+            tracing::warn!(error = %err, "Received invalid websocket message");
+        }
     }
+}
 
-    async fn send(&mut self, method: &str, params: impl IntoIterator<Item = &str>) -> u64 {
-        let mut params_str: String = params
-            .into_iter()
-            .map(|param| format!("\"{}\"", param))
-            .collect::<Vec<String>>()
-            .join(",");
+/// Settings of a supervised block subscription, see [`subscribe_blocks_supervised`].
+#[derive(Clone, Debug)]
+pub struct ReconnectPolicy {
+    /// Delay before the first reconnection attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between two reconnection attempts.
+    pub max_backoff: Duration,
+    /// Size of the time windows requested when backfilling missed blocks, in milliseconds.
+    pub backfill_step: i64,
+    /// How far before the last seen block the backfill starts, in milliseconds. Blocks of
+    /// different chains are not notified in timestamp order, so the overlap catches blocks
+    /// older than the last one seen which were still missed.
+    pub backfill_overlap: i64,
+    /// Number of block hashes remembered to drop duplicates.
+    pub dedup_capacity: usize,
+}
 
-        if !params_str.is_empty() {
-            params_str = format!("\"params\": [{params}],", params = params_str)
-        };
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            backfill_step: 60 * 1000,
+            backfill_overlap: 10 * 1000,
+            dedup_capacity: 4096,
+        }
+    }
+}
 
-        let id: u64 = self.id;
-        self.id += 1;
+impl ReconnectPolicy {
+    /// Returns the delay to wait before the given (1-based) reconnection attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(31);
+        self.initial_backoff.saturating_mul(1 << exp).min(self.max_backoff)
+    }
+}
 
-        let s: String = format!(
-            "{{\"method\":\"{method}\",{params}\"id\":{id}}}",
-            method = method,
-            params = params_str,
-            id = id
-        );
-        let message = Message::Text(s.into());
+/// Bounded set of the most recently seen block hashes.
+#[derive(Debug)]
+pub(crate) struct RecentHashes {
+    hashes: HashSet<String>,
+    order: VecDeque<String>,
+    capacity: usize,
+}
 
-        self.socket.send(message).await.unwrap();
+impl RecentHashes {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self { hashes: HashSet::new(), order: VecDeque::new(), capacity: capacity.max(1) }
+    }
 
-        id
+    /// Records a hash, returning false if it was already seen.
+    pub(crate) fn insert(&mut self, hash: &str) -> bool {
+        if self.hashes.contains(hash) {
+            return false;
+        }
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.hashes.remove(&oldest);
+            }
+        }
+        self.hashes.insert(hash.to_owned());
+        self.order.push_back(hash.to_owned());
+        true
     }
+}
+
+/// Subscribes to new blocks over a websocket connection that is kept alive in the background.
+///
+/// When the connection drops, it is re-established with backoff and the subscription renewed;
+/// blocks notified while disconnected are then fetched through
+/// [`Client::get_blocks_and_events`]. Blocks are de-duplicated by hash, so the stream is
+/// gap-free and yields each block once.
+///
+/// Every lost connection is reported as an `Err` item, after which the stream carries on with
+/// the reconnected feed. The background task stops once the stream is dropped.
+pub fn subscribe_blocks_supervised(
+    url: String,
+    client: Client,
+    policy: ReconnectPolicy,
+) -> BlockStream {
+    let (blocks_tx, blocks) = stream_channel::unbounded();
+    tokio::spawn(supervise_blocks(url, client, policy, blocks_tx));
+    blocks
+}
+
+struct SupervisedFeed {
+    blocks: stream_channel::UnboundedSender<Result<BlockEntry, WsError>>,
+    seen: RecentHashes,
+    last_timestamp: Option<i64>,
+}
 
-    pub async fn subscribe_blocks(&mut self) -> u64 {
-        self.send("block_notify", vec![]).await
+impl SupervisedFeed {
+    /// Forwards a block unless it was already seen.
+    fn emit(&mut self, block: BlockEntry) {
+        if !self.seen.insert(&block.hash) {
+            return;
+        }
+        self.last_timestamp =
+            Some(self.last_timestamp.map_or(block.timestamp, |ts| ts.max(block.timestamp)));
+        let _ = self.blocks.unbounded_send(Ok(block));
     }
 
-    pub async fn close(mut self) -> Result<(), Error> {
-        self.socket.close(None).await
+    fn report(&self, err: WsError) {
+        let _ = self.blocks.unbounded_send(Err(err));
     }
 }
 
-impl<T> From<ConnectionState<T>> for WebSocketStream<T> {
-    fn from(conn: ConnectionState<T>) -> WebSocketStream<T> {
-        conn.socket
+async fn supervise_blocks(
+    url: String,
+    client: Client,
+    policy: ReconnectPolicy,
+    blocks: stream_channel::UnboundedSender<Result<BlockEntry, WsError>>,
+) {
+    let mut feed = SupervisedFeed {
+        blocks,
+        seen: RecentHashes::new(policy.dedup_capacity),
+        last_timestamp: None,
+    };
+    let mut attempt = 0;
+
+    while !feed.blocks.is_closed() {
+        if attempt > 0 {
+            tokio::time::sleep(policy.backoff(attempt)).await;
+        }
+
+        let (ws, mut notified) = match connect_and_subscribe(&url).await {
+            Ok(connection) => connection,
+            Err(err) => {
+                attempt += 1;
+                tracing::warn!(url = url, attempt = attempt, error = %err, "Failed to connect to websocket");
+                if attempt == 1 {
+                    feed.report(err);
+                }
+                continue;
+            }
+        };
+        tracing::info!(url = url, "Subscribed to block notifications");
+
+        // Fetch whatever was notified while we were disconnected. Notifications received in the
+        // meantime are buffered by the connection and de-duplicated afterwards.
+        if let Some(last_timestamp) = feed.last_timestamp {
+            if let Err(err) = backfill(&client, &policy, last_timestamp, &mut feed).await {
+                attempt += 1;
+                tracing::warn!(error = %err, "Failed to backfill missed blocks");
+                feed.report(err);
+                let _ = ws.close().await;
+                continue;
+            }
+        }
+
+        let err = loop {
+            match notified.next().await {
+                Some(Ok(block)) => feed.emit(block),
+                Some(Err(err)) => break err,
+                None => break WsError::Closed,
+            }
+            if feed.blocks.is_closed() {
+                break WsError::Closed;
+            }
+        };
+        tracing::warn!(url = url, error = %err, "Lost websocket connection, reconnecting");
+        feed.report(err);
+        let _ = ws.close().await;
+        // The connection was up, start the backoff over
+        attempt = 1;
     }
 }
 
-impl<T> AsMut<WebSocketStream<T>> for ConnectionState<T> {
-    fn as_mut(&mut self) -> &mut WebSocketStream<T> {
-        &mut self.socket
+async fn connect_and_subscribe(url: &str) -> Result<(WsClient, BlockStream), WsError> {
+    let (mut ws, _) = WsClient::connect_async(url).await?;
+    let notified = ws.blocks().ok_or(WsError::Closed)?;
+    ws.subscribe_blocks().await?;
+    Ok((ws, notified))
+}
+
+/// Emits the blocks between `last_timestamp - backfill_overlap` and now.
+async fn backfill(
+    client: &Client,
+    policy: &ReconnectPolicy,
+    last_timestamp: i64,
+    feed: &mut SupervisedFeed,
+) -> Result<(), WsError> {
+    let to_ts = chrono::Utc::now().timestamp_millis();
+    let mut from_ts = last_timestamp - policy.backfill_overlap;
+    tracing::info!(from_ts = from_ts, to_ts = to_ts, "Backfilling missed blocks");
+
+    while from_ts <= to_ts {
+        let window_end = (from_ts + policy.backfill_step).min(to_ts);
+        let response = client.get_blocks_and_events(from_ts, window_end).await?;
+        let mut missed: Vec<BlockEntry> =
+            response.blocks_and_events.into_iter().flatten().map(|be| be.block).collect();
+        missed.sort_by_key(|block| block.timestamp);
+        for block in missed {
+            feed.emit(block);
+        }
+        from_ts = window_end + 1;
     }
+    Ok(())
 }
 
 pub struct Stream {
@@ -121,3 +495,126 @@ impl fmt::Display for Stream {
         write!(f, "{}", self.name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn block_json() -> Value {
+        json!({
+            "hash": "blockhash123",
+            "parent": "parent_hash",
+            "mainChain": true,
+            "timestamp": 1672531200,
+            "chainFrom": 1,
+            "chainTo": 2,
+            "height": 1000,
+            "deps": ["hash1", "hash2"],
+            "transactions": [],
+            "nonce": "nonce_value",
+            "version": 1,
+            "depStateHash": "dep_hash",
+            "txsHash": "txs_hash",
+            "target": "target_value",
+            "ghostUncles": []
+        })
+    }
+
+    #[test]
+    fn test_request_is_escaped() {
+        let request = Request {
+            jsonrpc: "2.0",
+            id: 7,
+            method: "subscribe",
+            params: vec![Value::from("a \"quoted\" param")],
+        };
+        let value: Value = serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+        assert_eq!(value["id"], 7);
+        assert_eq!(value["params"][0], "a \"quoted\" param");
+    }
+
+    #[test]
+    fn test_decode_block_notifications() {
+        let block = decode_block_notification(BLOCK_NOTIFY_METHOD, block_json()).unwrap().unwrap();
+        assert_eq!(block.hash, "blockhash123");
+
+        let params = json!({ "subscription": "0x01", "result": block_json() });
+        let block = decode_block_notification(SUBSCRIPTION_METHOD, params).unwrap().unwrap();
+        assert_eq!(block.height, 1000);
+
+        assert!(decode_block_notification("tx_notify", json!({})).is_none());
+        assert!(decode_block_notification(BLOCK_NOTIFY_METHOD, json!({})).unwrap().is_err());
+    }
+
+    #[test]
+    fn test_recent_hashes() {
+        let mut seen = RecentHashes::new(2);
+        assert!(seen.insert("a"));
+        assert!(!seen.insert("a"));
+        assert!(seen.insert("b"));
+        // Evicts "a", the oldest hash
+        assert!(seen.insert("c"));
+        assert!(seen.insert("a"));
+        assert!(!seen.insert("c"));
+    }
+
+    #[test]
+    fn test_reconnect_backoff() {
+        let policy = ReconnectPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(10), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_supervised_feed_deduplicates() {
+        let (blocks_tx, blocks_rx) = stream_channel::unbounded();
+        let mut feed =
+            SupervisedFeed { blocks: blocks_tx, seen: RecentHashes::new(16), last_timestamp: None };
+
+        let block: BlockEntry = serde_json::from_value(block_json()).unwrap();
+        let mut older = block.clone();
+        older.hash = "olderhash".to_string();
+        older.timestamp -= 1000;
+
+        feed.emit(block.clone());
+        feed.emit(older);
+        feed.emit(block);
+        drop(feed.blocks);
+
+        let hashes: Vec<String> = blocks_rx.map(|block| block.unwrap().hash).collect().await;
+        assert_eq!(hashes, vec!["blockhash123", "olderhash"]);
+        assert_eq!(feed.last_timestamp, Some(1672531200));
+    }
+
+    #[tokio::test]
+    async fn test_handle_text_routes_responses() {
+        let mut pending = HashMap::new();
+        let (blocks_tx, mut blocks_rx) = stream_channel::unbounded();
+
+        let (ok_tx, ok_rx) = oneshot::channel();
+        let (err_tx, err_rx) = oneshot::channel();
+        pending.insert(0, ok_tx);
+        pending.insert(1, err_tx);
+
+        handle_text(r#"{"jsonrpc":"2.0","id":0,"result":"0x01"}"#, &mut pending, &blocks_tx);
+        handle_text(
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"Method not found"}}"#,
+            &mut pending,
+            &blocks_tx,
+        );
+        let notification =
+            json!({ "jsonrpc": "2.0", "method": "block_notify", "params": block_json() });
+        handle_text(&notification.to_string(), &mut pending, &blocks_tx);
+
+        assert_eq!(ok_rx.await.unwrap().unwrap(), json!("0x01"));
+        assert!(matches!(err_rx.await.unwrap(), Err(WsError::Rpc { code: -32601, .. })));
+        assert!(pending.is_empty());
+        assert_eq!(blocks_rx.next().await.unwrap().unwrap().hash, "blockhash123");
+    }
+}